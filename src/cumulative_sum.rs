@@ -1,8 +1,9 @@
+use crate::math::{gcd, max, min};
 use cargo_snippet::snippet;
 
 #[snippet("cum_sum")]
 /// 1次元ベクタの累積和を取る
-pub fn cum_sum<T>(x: &mut Vec<T>)
+pub fn cum_sum<T>(x: &mut [T])
 where
     T: num::Num + Copy,
 {
@@ -17,7 +18,8 @@ where
 #[snippet(include = "cum_sum")]
 #[snippet("cum_sum_2d")]
 /// 2次元ベクタの累積和を取る
-pub fn cum_sum_2d<T>(x: &mut Vec<Vec<T>>)
+#[allow(clippy::needless_range_loop)]
+pub fn cum_sum_2d<T>(x: &mut [Vec<T>])
 where
     T: num::Num + Copy,
 {
@@ -38,6 +40,635 @@ where
     }
 }
 
+#[snippet(include = "cum_sum_2d")]
+#[snippet("cum_sum_3d")]
+/// 3次元ベクタの累積和を取る
+pub fn cum_sum_3d<T>(x: &mut [Vec<Vec<T>>])
+where
+    T: num::Num + Copy,
+{
+    if x.is_empty() || x[0].is_empty() || x[0][0].is_empty() {
+        return;
+    }
+    let d = x.len();
+    // 各層で2次元の累積和を取る
+    for xx in x.iter_mut() {
+        cum_sum_2d(xx);
+    }
+    // 層方向に累積和を取る
+    for i in 0..(d - 1) {
+        let (upper, lower) = x.split_at_mut(i + 1);
+        for (a, b) in lower[0].iter_mut().zip(upper[i].iter()) {
+            for (aa, &bb) in a.iter_mut().zip(b.iter()) {
+                *aa = *aa + bb;
+            }
+        }
+    }
+}
+
+#[snippet("range_to_pair")]
+/// 範囲を長さnの列における半開区間[l, r)に変換する
+pub fn range_to_pair<R>(range: R, n: usize) -> (usize, usize)
+where
+    R: std::ops::RangeBounds<usize>,
+{
+    use std::ops::Bound;
+    let l = match range.start_bound() {
+        Bound::Included(&l) => l,
+        Bound::Excluded(&l) => l + 1,
+        Bound::Unbounded => 0,
+    };
+    let r = match range.end_bound() {
+        Bound::Included(&r) => r + 1,
+        Bound::Excluded(&r) => r,
+        Bound::Unbounded => n,
+    };
+    assert!(l <= r && r <= n, "invalid range: {}..{} (len {})", l, r, n);
+    (l, r)
+}
+
+#[snippet("CumSum")]
+#[snippet(include = "cum_sum")]
+#[snippet(include = "range_to_pair")]
+/// 1次元累積和. 元の列は変更しない
+pub struct CumSum<T> {
+    // data[i] = x[0] + ... + x[i - 1]
+    data: Vec<T>,
+}
+
+#[snippet("CumSum")]
+impl<T> CumSum<T>
+where
+    T: num::Num + Copy,
+{
+    pub fn new(x: &[T]) -> Self {
+        let mut data = vec![T::zero(); x.len() + 1];
+        data[1..].copy_from_slice(x);
+        cum_sum(&mut data);
+        Self { data }
+    }
+
+    /// 範囲内の要素の総和を求める
+    pub fn sum<R>(&self, range: R) -> T
+    where
+        R: std::ops::RangeBounds<usize>,
+    {
+        let (l, r) = range_to_pair(range, self.data.len() - 1);
+        self.data[r] - self.data[l]
+    }
+}
+
+#[snippet("CumSum2D")]
+#[snippet(include = "cum_sum_2d")]
+#[snippet(include = "range_to_pair")]
+/// 2次元累積和. 元の列は変更しない
+pub struct CumSum2D<T> {
+    // data[i][j] = x[0..i][0..j]の総和
+    data: Vec<Vec<T>>,
+}
+
+#[snippet("CumSum2D")]
+impl<T> CumSum2D<T>
+where
+    T: num::Num + Copy,
+{
+    pub fn new(x: &[Vec<T>]) -> Self {
+        let h = x.len();
+        let w = if h == 0 { 0 } else { x[0].len() };
+        let mut data = vec![vec![T::zero(); w + 1]; h + 1];
+        for (d, xx) in data[1..].iter_mut().zip(x.iter()) {
+            d[1..].copy_from_slice(xx);
+        }
+        cum_sum_2d(&mut data);
+        Self { data }
+    }
+
+    /// 長方形領域内の要素の総和を求める
+    pub fn rect_sum<R, C>(&self, rows: R, cols: C) -> T
+    where
+        R: std::ops::RangeBounds<usize>,
+        C: std::ops::RangeBounds<usize>,
+    {
+        let (r0, r1) = range_to_pair(rows, self.data.len() - 1);
+        let (c0, c1) = range_to_pair(cols, self.data[0].len() - 1);
+        // 符号なし整数でも負の途中結果を作らない順で計算する
+        (self.data[r1][c1] + self.data[r0][c0]) - self.data[r0][c1] - self.data[r1][c0]
+    }
+}
+
+#[snippet("diff")]
+/// 1次元ベクタの差分を取る（cum_sumの逆操作）
+pub fn diff<T>(x: &mut [T])
+where
+    T: num::Num + Copy,
+{
+    for i in (1..x.len()).rev() {
+        x[i] = x[i] - x[i - 1];
+    }
+}
+
+#[snippet("Imos1D")]
+#[snippet(include = "cum_sum")]
+#[snippet(include = "range_to_pair")]
+/// 1次元のいもす法. 区間加算をまとめて行う. 差分は負になりうるため符号付きの型を使う
+pub struct Imos1D<T> {
+    n: usize,
+    // 差分. 区間の右端を扱うために要素数をn+1とする
+    data: Vec<T>,
+}
+
+#[snippet("Imos1D")]
+impl<T> Imos1D<T>
+where
+    T: num::Signed + Copy,
+{
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            data: vec![T::zero(); n + 1],
+        }
+    }
+
+    /// 範囲内の各要素にvを加える
+    pub fn add<R>(&mut self, range: R, v: T)
+    where
+        R: std::ops::RangeBounds<usize>,
+    {
+        let (l, r) = range_to_pair(range, self.n);
+        self.data[l] = self.data[l] + v;
+        self.data[r] = self.data[r] - v;
+    }
+
+    /// 加算を反映した列を求める
+    pub fn build(&self) -> Vec<T> {
+        let mut x = self.data[..self.n].to_vec();
+        cum_sum(&mut x);
+        x
+    }
+}
+
+#[snippet("Imos2D")]
+#[snippet(include = "cum_sum_2d")]
+#[snippet(include = "range_to_pair")]
+/// 2次元のいもす法. 長方形領域への加算をまとめて行う. 差分は負になりうるため符号付きの型を使う
+pub struct Imos2D<T> {
+    h: usize,
+    w: usize,
+    // 差分. 領域の端を扱うために(h+1)×(w+1)とする
+    data: Vec<Vec<T>>,
+}
+
+#[snippet("Imos2D")]
+impl<T> Imos2D<T>
+where
+    T: num::Signed + Copy,
+{
+    pub fn new(h: usize, w: usize) -> Self {
+        Self {
+            h,
+            w,
+            data: vec![vec![T::zero(); w + 1]; h + 1],
+        }
+    }
+
+    /// 長方形領域内の各要素にvを加える
+    pub fn add_rect<R, C>(&mut self, rows: R, cols: C, v: T)
+    where
+        R: std::ops::RangeBounds<usize>,
+        C: std::ops::RangeBounds<usize>,
+    {
+        let (r0, r1) = range_to_pair(rows, self.h);
+        let (c0, c1) = range_to_pair(cols, self.w);
+        self.data[r0][c0] = self.data[r0][c0] + v;
+        self.data[r0][c1] = self.data[r0][c1] - v;
+        self.data[r1][c0] = self.data[r1][c0] - v;
+        self.data[r1][c1] = self.data[r1][c1] + v;
+    }
+
+    /// 加算を反映した2次元ベクタを求める
+    pub fn build(&self) -> Vec<Vec<T>> {
+        let mut x: Vec<Vec<T>> = self.data[..self.h]
+            .iter()
+            .map(|row| row[..self.w].to_vec())
+            .collect();
+        cum_sum_2d(&mut x);
+        x
+    }
+}
+
+#[snippet("CumSum3D")]
+#[snippet(include = "cum_sum_3d")]
+#[snippet(include = "range_to_pair")]
+/// 3次元累積和. 元の列は変更しない
+pub struct CumSum3D<T> {
+    // data[i][j][k] = x[0..i][0..j][0..k]の総和
+    data: Vec<Vec<Vec<T>>>,
+}
+
+#[snippet("CumSum3D")]
+impl<T> CumSum3D<T>
+where
+    T: num::Num + Copy,
+{
+    pub fn new(x: &[Vec<Vec<T>>]) -> Self {
+        let d = x.len();
+        let h = if d == 0 { 0 } else { x[0].len() };
+        let w = if h == 0 { 0 } else { x[0][0].len() };
+        let mut data = vec![vec![vec![T::zero(); w + 1]; h + 1]; d + 1];
+        for (dd, xx) in data[1..].iter_mut().zip(x.iter()) {
+            for (ddd, xxx) in dd[1..].iter_mut().zip(xx.iter()) {
+                ddd[1..].copy_from_slice(xxx);
+            }
+        }
+        cum_sum_3d(&mut data);
+        Self { data }
+    }
+
+    /// 直方体領域内の要素の総和を求める
+    pub fn box_sum<R0, R1, R2>(&self, r0: R0, r1: R1, r2: R2) -> T
+    where
+        R0: std::ops::RangeBounds<usize>,
+        R1: std::ops::RangeBounds<usize>,
+        R2: std::ops::RangeBounds<usize>,
+    {
+        let (a0, a1) = range_to_pair(r0, self.data.len() - 1);
+        let (b0, b1) = range_to_pair(r1, self.data[0].len() - 1);
+        let (c0, c1) = range_to_pair(r2, self.data[0][0].len() - 1);
+        let d = &self.data;
+        // 符号なし整数でも負の途中結果を作らないよう、正の項と負の項を分けて足す
+        let pos = d[a1][b1][c1] + d[a0][b0][c1] + d[a0][b1][c0] + d[a1][b0][c0];
+        let neg = d[a0][b1][c1] + d[a1][b0][c1] + d[a1][b1][c0] + d[a0][b0][c0];
+        pos - neg
+    }
+}
+
+#[snippet("CumSumND")]
+#[snippet(include = "range_to_pair")]
+/// N次元累積和. 要素は行優先で1次元に並べて与える
+pub struct CumSumND<T> {
+    // 各次元の大きさ+1
+    shape: Vec<usize>,
+    // 各次元のインデックスが1増えたときの、dataにおけるインデックスの増分
+    strides: Vec<usize>,
+    data: Vec<T>,
+}
+
+#[snippet("CumSumND")]
+impl<T> CumSumND<T>
+where
+    T: num::Num + Copy,
+{
+    pub fn new(x: &[T], shape: &[usize]) -> Self {
+        assert_eq!(x.len(), shape.iter().product::<usize>());
+        let shape: Vec<usize> = shape.iter().map(|&s| s + 1).collect();
+        let mut strides = vec![1; shape.len()];
+        for i in (0..shape.len().saturating_sub(1)).rev() {
+            strides[i] = strides[i + 1] * shape[i + 1];
+        }
+        let size = shape.iter().product::<usize>();
+        let mut data = vec![T::zero(); size];
+        // 各次元のインデックスを1ずらして配置する
+        if !x.is_empty() {
+            let offset: usize = strides.iter().sum();
+            let mut idx = vec![0; shape.len()];
+            for &v in x.iter() {
+                let pos: usize = idx.iter().zip(strides.iter()).map(|(i, s)| i * s).sum();
+                data[pos + offset] = v;
+                for k in (0..shape.len()).rev() {
+                    idx[k] += 1;
+                    if idx[k] + 1 < shape[k] {
+                        break;
+                    }
+                    idx[k] = 0;
+                }
+            }
+        }
+        // 次元ごとに累積和を取る
+        for (k, &stride) in strides.iter().enumerate() {
+            for i in 0..size {
+                if (i / stride) % shape[k] > 0 {
+                    data[i] = data[i] + data[i - stride];
+                }
+            }
+        }
+        Self {
+            shape,
+            strides,
+            data,
+        }
+    }
+
+    /// 各次元の範囲で指定した領域内の要素の総和を求める（包除原理）
+    pub fn sum<R>(&self, ranges: &[R]) -> T
+    where
+        R: std::ops::RangeBounds<usize>,
+    {
+        let n = self.shape.len();
+        assert_eq!(ranges.len(), n);
+        let pairs: Vec<(usize, usize)> = ranges
+            .iter()
+            .zip(self.shape.iter())
+            .map(|(r, &s)| range_to_pair((r.start_bound().cloned(), r.end_bound().cloned()), s - 1))
+            .collect();
+        // box_sumと同様に、正の項と負の項を分けて足す
+        let mut pos = T::zero();
+        let mut neg = T::zero();
+        for mask in 0..(1usize << n) {
+            let mut idx = 0;
+            for (k, &(l, r)) in pairs.iter().enumerate() {
+                let i = if mask >> k & 1 == 1 { l } else { r };
+                idx += i * self.strides[k];
+            }
+            if mask.count_ones() % 2 == 0 {
+                pos = pos + self.data[idx];
+            } else {
+                neg = neg + self.data[idx];
+            }
+        }
+        pos - neg
+    }
+}
+
+#[snippet("monoid")]
+/// モノイド. 二項演算opは結合則を満たし、identityはその単位元であること
+pub trait Monoid {
+    type S: Clone;
+    /// 単位元
+    fn identity() -> Self::S;
+    /// 二項演算
+    fn op(a: &Self::S, b: &Self::S) -> Self::S;
+}
+
+#[snippet("Additive")]
+#[snippet(include = "monoid")]
+/// 加算のモノイド
+pub struct Additive<T>(std::marker::PhantomData<T>);
+
+#[snippet("Additive")]
+impl<T> Monoid for Additive<T>
+where
+    T: num::Num + Copy,
+{
+    type S = T;
+    fn identity() -> T {
+        T::zero()
+    }
+    fn op(a: &T, b: &T) -> T {
+        *a + *b
+    }
+}
+
+#[snippet("Xor")]
+#[snippet(include = "monoid")]
+/// 排他的論理和のモノイド
+pub struct Xor<T>(std::marker::PhantomData<T>);
+
+#[snippet("Xor")]
+impl<T> Monoid for Xor<T>
+where
+    T: num::PrimInt,
+{
+    type S = T;
+    fn identity() -> T {
+        T::zero()
+    }
+    fn op(a: &T, b: &T) -> T {
+        *a ^ *b
+    }
+}
+
+#[snippet("Min")]
+#[snippet(include = "monoid")]
+#[snippet(include = "min")]
+/// 最小値のモノイド
+pub struct Min<T>(std::marker::PhantomData<T>);
+
+#[snippet("Min")]
+impl<T> Monoid for Min<T>
+where
+    T: num::Bounded + PartialOrd + Copy,
+{
+    type S = T;
+    fn identity() -> T {
+        T::max_value()
+    }
+    fn op(a: &T, b: &T) -> T {
+        min(*a, *b)
+    }
+}
+
+#[snippet("Max")]
+#[snippet(include = "monoid")]
+#[snippet(include = "max")]
+/// 最大値のモノイド
+pub struct Max<T>(std::marker::PhantomData<T>);
+
+#[snippet("Max")]
+impl<T> Monoid for Max<T>
+where
+    T: num::Bounded + PartialOrd + Copy,
+{
+    type S = T;
+    fn identity() -> T {
+        T::min_value()
+    }
+    fn op(a: &T, b: &T) -> T {
+        max(*a, *b)
+    }
+}
+
+#[snippet("Gcd")]
+#[snippet(include = "monoid")]
+#[snippet(include = "gcd")]
+/// 最大公約数のモノイド. 単位元は0
+pub struct Gcd<T>(std::marker::PhantomData<T>);
+
+#[snippet("Gcd")]
+impl<T> Monoid for Gcd<T>
+where
+    T: num::Unsigned + Copy,
+{
+    type S = T;
+    fn identity() -> T {
+        T::zero()
+    }
+    fn op(a: &T, b: &T) -> T {
+        gcd(*a, *b)
+    }
+}
+
+#[snippet("ProdMod")]
+#[snippet(include = "monoid")]
+/// Pを法とする乗算のモノイド
+pub struct ProdMod<const P: u64>;
+
+#[snippet("ProdMod")]
+impl<const P: u64> Monoid for ProdMod<P> {
+    type S = u64;
+    fn identity() -> u64 {
+        1 % P
+    }
+    fn op(a: &u64, b: &u64) -> u64 {
+        // Pが2^32以上でもオーバーフローしないようu128で計算する
+        (*a as u128 * *b as u128 % P as u128) as u64
+    }
+}
+
+#[snippet("prefix_fold")]
+#[snippet(include = "monoid")]
+/// 接頭辞の総積を求める. 返り値のi番目はx[0..i]の総積（要素数はn+1）
+/// 加算の場合はcum_sumの結果の先頭に0を加えたものになる（cum_sumはx[0..=i]の総和をn個返す）
+pub fn prefix_fold<M>(x: &[M::S]) -> Vec<M::S>
+where
+    M: Monoid,
+{
+    let mut res = Vec::with_capacity(x.len() + 1);
+    res.push(M::identity());
+    for (i, v) in x.iter().enumerate() {
+        res.push(M::op(&res[i], v));
+    }
+    res
+}
+
+#[snippet("suffix_fold")]
+#[snippet(include = "monoid")]
+/// 接尾辞の総積を求める. 返り値のi番目はx[i..]の総積（要素数はn+1）
+pub fn suffix_fold<M>(x: &[M::S]) -> Vec<M::S>
+where
+    M: Monoid,
+{
+    let n = x.len();
+    let mut res = vec![M::identity(); n + 1];
+    for i in (0..n).rev() {
+        res[i] = M::op(&x[i], &res[i + 1]);
+    }
+    res
+}
+
+#[snippet("sliding_window")]
+/// 幅kの各窓について、better(a, b)がtrueとなるような最良の要素を求める（スライド最小値）
+fn sliding_window_by<T>(x: &[T], k: usize, better: fn(&T, &T) -> bool) -> Vec<T>
+where
+    T: Copy,
+{
+    assert!(k > 0, "window size must be positive");
+    let mut res = vec![];
+    // 窓内の要素のインデックス. 先頭が最良となるように単調性を保つ
+    let mut deque: std::collections::VecDeque<usize> = std::collections::VecDeque::new();
+    for (i, v) in x.iter().enumerate() {
+        while let Some(&j) = deque.back() {
+            if better(v, &x[j]) {
+                deque.pop_back();
+            } else {
+                break;
+            }
+        }
+        deque.push_back(i);
+        if deque[0] + k <= i {
+            deque.pop_front();
+        }
+        if i + 1 >= k {
+            res.push(x[deque[0]]);
+        }
+    }
+    res
+}
+
+#[snippet("sliding_window")]
+/// 幅kの各窓の最小値を求める. 返り値のi番目はx[i..i + k]の最小値
+pub fn sliding_window_min<T>(x: &[T], k: usize) -> Vec<T>
+where
+    T: PartialOrd + Copy,
+{
+    sliding_window_by(x, k, |a, b| a <= b)
+}
+
+#[snippet("sliding_window")]
+/// 幅kの各窓の最大値を求める. 返り値のi番目はx[i..i + k]の最大値
+pub fn sliding_window_max<T>(x: &[T], k: usize) -> Vec<T>
+where
+    T: PartialOrd + Copy,
+{
+    sliding_window_by(x, k, |a, b| a >= b)
+}
+
+#[snippet("FoldableQueue")]
+#[snippet(include = "monoid")]
+/// 総積を取得できるキュー（Sliding Window Aggregation）. 各操作は償却O(1)
+pub struct FoldableQueue<M>
+where
+    M: Monoid,
+{
+    // 先頭側のスタック. (値, その値から末尾側のスタックの底までの総積)
+    front: Vec<(M::S, M::S)>,
+    // 末尾側のスタック
+    back: Vec<M::S>,
+    // 末尾側のスタックの総積
+    back_fold: M::S,
+}
+
+#[snippet("FoldableQueue")]
+impl<M> FoldableQueue<M>
+where
+    M: Monoid,
+{
+    pub fn new() -> Self {
+        Self {
+            front: vec![],
+            back: vec![],
+            back_fold: M::identity(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 末尾に追加する
+    pub fn push(&mut self, x: M::S) {
+        self.back_fold = M::op(&self.back_fold, &x);
+        self.back.push(x);
+    }
+
+    /// 先頭から取り出す
+    pub fn pop(&mut self) -> Option<M::S> {
+        if self.front.is_empty() {
+            // 末尾側のスタックを先頭側に積み替える
+            while let Some(x) = self.back.pop() {
+                let f = match self.front.last() {
+                    Some((_, f)) => M::op(&x, f),
+                    None => x.clone(),
+                };
+                self.front.push((x, f));
+            }
+            self.back_fold = M::identity();
+        }
+        self.front.pop().map(|(x, _)| x)
+    }
+
+    /// 全要素の総積を求める
+    pub fn fold(&self) -> M::S {
+        match self.front.last() {
+            Some((_, f)) => M::op(f, &self.back_fold),
+            None => self.back_fold.clone(),
+        }
+    }
+}
+
+#[snippet("FoldableQueue")]
+impl<M> Default for FoldableQueue<M>
+where
+    M: Monoid,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -76,4 +707,291 @@ mod tests {
         let expected: Vec<Vec<i32>> = vec![];
         assert_eq!(x4, expected);
     }
+
+    #[test]
+    fn test_cum_sum_struct() {
+        let x = vec![1i64, 2, 3, 4, 5];
+        let cs = CumSum::new(&x);
+        assert_eq!(cs.sum(..), 15);
+        assert_eq!(cs.sum(1..4), 9);
+        assert_eq!(cs.sum(1..=3), 9);
+        assert_eq!(cs.sum(..2), 3);
+        assert_eq!(cs.sum(3..), 9);
+        assert_eq!(cs.sum(2..2), 0);
+        // 元の列は変更されない
+        assert_eq!(x, vec![1, 2, 3, 4, 5]);
+
+        let cs = CumSum::new(&[7usize]);
+        assert_eq!(cs.sum(..), 7);
+        assert_eq!(cs.sum(0..0), 0);
+
+        let cs = CumSum::<i32>::new(&[]);
+        assert_eq!(cs.sum(..), 0);
+    }
+
+    #[test]
+    fn test_cum_sum_2d_struct() {
+        let x: Vec<Vec<i64>> = vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]];
+        let cs = CumSum2D::new(&x);
+        assert_eq!(cs.rect_sum(.., ..), 45);
+        assert_eq!(cs.rect_sum(1..3, 1..3), 28);
+        assert_eq!(cs.rect_sum(0..=1, 2..), 9);
+        assert_eq!(cs.rect_sum(1..1, ..), 0);
+
+        let cs = CumSum2D::new(&[vec![1usize, 2, 3]]);
+        assert_eq!(cs.rect_sum(.., 1..), 5);
+
+        // 符号なし整数で途中の値が負にならないこと
+        let cs = CumSum2D::<usize>::new(&[vec![10, 0], vec![0, 0]]);
+        assert_eq!(cs.rect_sum(1..2, 1..2), 0);
+        assert_eq!(cs.rect_sum(.., 1..), 0);
+
+        let cs = CumSum2D::<i32>::new(&[vec![]]);
+        assert_eq!(cs.rect_sum(.., ..), 0);
+
+        let cs = CumSum2D::<i32>::new(&[]);
+        assert_eq!(cs.rect_sum(.., ..), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cum_sum_struct_panic() {
+        // 範囲外
+        CumSum::new(&[1, 2, 3]).sum(1..4);
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut x1: Vec<i64> = vec![1, 3, 6, 10, 15];
+        diff(&mut x1);
+        assert_eq!(x1, vec![1, 2, 3, 4, 5]);
+
+        // cum_sumの逆操作になっていること
+        let mut x2: Vec<i32> = vec![3, -1, 4, 1, -5];
+        cum_sum(&mut x2);
+        diff(&mut x2);
+        assert_eq!(x2, vec![3, -1, 4, 1, -5]);
+
+        let mut x3: Vec<i32> = Vec::new();
+        diff(&mut x3);
+        assert_eq!(x3, vec![]);
+    }
+
+    #[test]
+    fn test_imos_1d() {
+        let mut imos: Imos1D<i64> = Imos1D::new(5);
+        imos.add(1..4, 2);
+        imos.add(..2, 1);
+        imos.add(3..=4, -3);
+        imos.add(2..2, 100);
+        assert_eq!(imos.build(), vec![1, 3, 2, -1, -3]);
+
+        let imos: Imos1D<i64> = Imos1D::new(0);
+        assert_eq!(imos.build(), vec![]);
+    }
+
+    #[test]
+    fn test_imos_2d() {
+        let mut imos: Imos2D<i64> = Imos2D::new(3, 4);
+        imos.add_rect(0..2, 1..3, 1);
+        imos.add_rect(1.., 2.., 2);
+        assert_eq!(
+            imos.build(),
+            vec![vec![0, 1, 1, 0], vec![0, 1, 3, 2], vec![0, 0, 2, 2]]
+        );
+
+        let imos: Imos2D<i64> = Imos2D::new(2, 0);
+        assert_eq!(imos.build(), vec![vec![], vec![]]);
+    }
+
+    #[test]
+    fn test_cum_sum_3d() {
+        let mut x1: Vec<Vec<Vec<i64>>> =
+            vec![vec![vec![1, 2], vec![3, 4]], vec![vec![5, 6], vec![7, 8]]];
+        cum_sum_3d(&mut x1);
+        assert_eq!(
+            x1,
+            vec![
+                vec![vec![1, 3], vec![4, 10]],
+                vec![vec![6, 14], vec![16, 36]],
+            ]
+        );
+
+        let mut x2: Vec<Vec<Vec<i32>>> = vec![vec![vec![]]];
+        cum_sum_3d(&mut x2);
+        assert_eq!(x2, vec![vec![vec![]]]);
+
+        let mut x3: Vec<Vec<Vec<i32>>> = vec![];
+        cum_sum_3d(&mut x3);
+        let expected: Vec<Vec<Vec<i32>>> = vec![];
+        assert_eq!(x3, expected);
+    }
+
+    #[test]
+    fn test_cum_sum_3d_struct() {
+        // x[i][j][k] = 100i + 10j + k
+        let x: Vec<Vec<Vec<i64>>> = (0..3)
+            .map(|i| {
+                (0..4)
+                    .map(|j| (0..5).map(|k| 100 * i + 10 * j + k).collect())
+                    .collect()
+            })
+            .collect();
+        let cs = CumSum3D::new(&x);
+        for (r0, r1, r2) in [(0..3, 0..4, 0..5), (1..3, 2..3, 1..4), (2..2, 0..4, 0..5)] {
+            let mut expected = 0;
+            for i in r0.clone() {
+                for j in r1.clone() {
+                    for k in r2.clone() {
+                        expected += x[i][j][k];
+                    }
+                }
+            }
+            assert_eq!(cs.box_sum(r0, r1, r2), expected);
+        }
+        assert_eq!(cs.box_sum(..=0, ..=0, ..), 10);
+
+        let cs = CumSum3D::<i32>::new(&[]);
+        assert_eq!(cs.box_sum(.., .., ..), 0);
+
+        // 符号なし整数で途中の値が負にならないこと
+        let x: Vec<Vec<Vec<usize>>> =
+            vec![vec![vec![10, 0], vec![0, 0]], vec![vec![0, 0], vec![0, 1]]];
+        let cs = CumSum3D::new(&x);
+        assert_eq!(cs.box_sum(1.., 1.., 1..), 1);
+        assert_eq!(cs.box_sum(.., 1.., ..), 1);
+    }
+
+    #[test]
+    fn test_cum_sum_nd() {
+        // 2次元の場合にCumSum2Dと一致すること
+        let x: Vec<Vec<i64>> = vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]];
+        let flat: Vec<i64> = x.concat();
+        let cs = CumSumND::new(&flat, &[3, 3]);
+        let cs2 = CumSum2D::new(&x);
+        for r0 in 0..=3 {
+            for r1 in r0..=3 {
+                for c0 in 0..=3 {
+                    for c1 in c0..=3 {
+                        assert_eq!(cs.sum(&[r0..r1, c0..c1]), cs2.rect_sum(r0..r1, c0..c1));
+                    }
+                }
+            }
+        }
+
+        // 4次元
+        let shape = [2, 3, 2, 2];
+        let flat: Vec<i64> = (0..24).collect();
+        let cs = CumSumND::new(&flat, &shape);
+        assert_eq!(cs.sum(&[0..2, 0..3, 0..2, 0..2]), 276);
+        // (1, 2, 1, 0) の要素のみ
+        assert_eq!(cs.sum(&[1..2, 2..3, 1..2, 0..1]), 22);
+        // (*, 1, *, 1) の要素
+        assert_eq!(cs.sum(&[0..2, 1..2, 0..2, 1..2]), 5 + 7 + 17 + 19);
+
+        // 1次元
+        let cs = CumSumND::new(&[1, 2, 3], &[3]);
+        assert_eq!(cs.sum(&[1..=2]), 5);
+        assert_eq!(cs.sum(&[1..=1]), 2);
+        assert_eq!(cs.sum(&[..]), 6);
+
+        // 空
+        let cs = CumSumND::<i32>::new(&[], &[0, 3]);
+        assert_eq!(cs.sum(&[0..0, 0..3]), 0);
+
+        // 符号なし整数で途中の値が負にならないこと
+        let cs = CumSumND::<usize>::new(&[10, 0, 0, 0, 0, 0, 0, 1], &[2, 2, 2]);
+        assert_eq!(cs.sum(&[1..2, 1..2, 1..2]), 1);
+        assert_eq!(cs.sum(&[0..2, 1..2, 0..2]), 1);
+    }
+
+    #[test]
+    fn test_prefix_fold() {
+        let x: Vec<i64> = vec![3, 1, 4, 1, 5];
+        assert_eq!(prefix_fold::<Additive<i64>>(&x), vec![0, 3, 4, 8, 9, 14]);
+        assert_eq!(prefix_fold::<Min<i64>>(&x), vec![i64::MAX, 3, 1, 1, 1, 1]);
+        assert_eq!(prefix_fold::<Max<i64>>(&x), vec![i64::MIN, 3, 3, 4, 4, 5]);
+        assert_eq!(prefix_fold::<Xor<i64>>(&x), vec![0, 3, 2, 6, 7, 2]);
+
+        let y: Vec<u64> = vec![12, 18, 8, 5];
+        assert_eq!(prefix_fold::<Gcd<u64>>(&y), vec![0, 12, 6, 2, 1]);
+        assert_eq!(prefix_fold::<ProdMod<7>>(&y), vec![1, 5, 6, 6, 2]);
+        // 法が2^32以上
+        const P: u64 = (1 << 61) - 1;
+        let z: Vec<u64> = vec![P - 1, P - 1, P - 2];
+        assert_eq!(prefix_fold::<ProdMod<P>>(&z), vec![1, P - 1, 1, P - 2]);
+
+        // 空
+        assert_eq!(prefix_fold::<Additive<i32>>(&[]), vec![0]);
+    }
+
+    #[test]
+    fn test_suffix_fold() {
+        let x: Vec<i64> = vec![3, 1, 4, 1, 5];
+        assert_eq!(suffix_fold::<Additive<i64>>(&x), vec![14, 11, 10, 6, 5, 0]);
+        assert_eq!(suffix_fold::<Min<i64>>(&x), vec![1, 1, 1, 1, 5, i64::MAX]);
+        assert_eq!(suffix_fold::<Max<i64>>(&x), vec![5, 5, 5, 5, 5, i64::MIN]);
+
+        let y: Vec<u64> = vec![12, 18, 8, 5];
+        assert_eq!(suffix_fold::<Gcd<u64>>(&y), vec![1, 1, 1, 5, 0]);
+
+        // 空
+        assert_eq!(suffix_fold::<Max<i32>>(&[]), vec![i32::MIN]);
+    }
+
+    #[test]
+    fn test_sliding_window() {
+        let x: Vec<i64> = vec![3, 1, 4, 1, 5, 9, 2, 6];
+        assert_eq!(sliding_window_min(&x, 3), vec![1, 1, 1, 1, 2, 2]);
+        assert_eq!(sliding_window_max(&x, 3), vec![4, 4, 5, 9, 9, 9]);
+        // 幅1
+        assert_eq!(sliding_window_min(&x, 1), x);
+        // 幅が要素数と等しい
+        assert_eq!(sliding_window_max(&x, 8), vec![9]);
+        // 幅が要素数より大きい
+        assert_eq!(sliding_window_min(&x, 9), vec![]);
+        // 空
+        assert_eq!(sliding_window_max(&Vec::<i32>::new(), 2), vec![]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sliding_window_panic() {
+        sliding_window_min(&[1, 2, 3], 0);
+    }
+
+    #[test]
+    fn test_foldable_queue() {
+        let mut q: FoldableQueue<Additive<i64>> = FoldableQueue::new();
+        assert_eq!(q.fold(), 0);
+        assert_eq!(q.pop(), None);
+        q.push(1);
+        q.push(2);
+        q.push(3);
+        assert_eq!(q.fold(), 6);
+        assert_eq!(q.pop(), Some(1));
+        q.push(4);
+        assert_eq!(q.len(), 3);
+        assert_eq!(q.fold(), 9);
+        assert_eq!(q.pop(), Some(2));
+        assert_eq!(q.pop(), Some(3));
+        assert_eq!(q.fold(), 4);
+        assert_eq!(q.pop(), Some(4));
+        assert!(q.is_empty());
+
+        // 非可換な演算で順序が保たれること（窓幅3の一次関数の合成）
+        let mut q: FoldableQueue<crate::segment_tree::Affine<i64>> = FoldableQueue::new();
+        let fs: Vec<(i64, i64)> = vec![(2, 1), (3, 0), (1, -4), (5, 2), (1, 1)];
+        let mut res = vec![];
+        for (i, &f) in fs.iter().enumerate() {
+            q.push(f);
+            if i >= 3 {
+                q.pop();
+            }
+            if i >= 2 {
+                res.push(q.fold());
+            }
+        }
+        assert_eq!(res, vec![(6, -1), (15, -18), (5, -17)]);
+    }
 }
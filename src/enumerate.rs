@@ -49,8 +49,8 @@ mod tests {
             assert_eq!(src, dst);
         }
         // 要素が1つだけの場合
-        assert_eq!(next_permutaion(&mut vec![1]), false);
+        assert!(!next_permutaion(&mut [1]));
         // 空の場合
-        assert_eq!(next_permutaion(&mut Vec::<i32>::new()), false);
+        assert!(!next_permutaion(&mut Vec::<i32>::new()));
     }
 }
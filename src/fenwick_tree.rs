@@ -0,0 +1,456 @@
+use cargo_snippet::snippet;
+
+#[snippet("fenwick-tree")]
+/// Fenwick木（BIT）. インデックスは0-origin
+/// 演算は可換群（単位元e, 二項演算op, 逆元inv）であること
+pub struct FenwickTree<T> {
+    n: usize,
+    // 内部では1-originで管理する
+    data: Vec<T>,
+    e: T,
+    op: fn(T, T) -> T,
+    inv: fn(T) -> T,
+    // 区間和を求める際の差. Noneの場合はop(a, inv(b))で求める
+    // 加算の場合はa - bとし、符号なし整数でも負の途中結果を作らないようにする
+    sub: Option<fn(T, T) -> T>,
+}
+
+#[snippet("fenwick-tree")]
+impl<T> FenwickTree<T>
+where
+    T: num::Num + Copy,
+{
+    /// 加算を演算とするFenwick木を作る
+    pub fn new(n: usize) -> Self {
+        let mut ft = Self::with_op(n, T::zero(), |a, b| a + b, |a| T::zero() - a);
+        ft.sub = Some(|a, b| a - b);
+        ft
+    }
+}
+
+#[snippet("fenwick-tree")]
+impl<T> FenwickTree<T>
+where
+    T: Copy,
+{
+    /// 任意の可換群を演算とするFenwick木を作る
+    pub fn with_op(n: usize, e: T, op: fn(T, T) -> T, inv: fn(T) -> T) -> Self {
+        Self {
+            n,
+            data: vec![e; n + 1],
+            e,
+            op,
+            inv,
+            sub: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// i番目の要素にxを作用させる
+    pub fn add(&mut self, i: usize, x: T) {
+        let mut i = i + 1;
+        while i <= self.n {
+            self.data[i] = (self.op)(self.data[i], x);
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// [0, r)の総和を求める
+    pub fn sum(&self, r: usize) -> T {
+        let mut i = r;
+        let mut s = self.e;
+        while i > 0 {
+            s = (self.op)(s, self.data[i]);
+            i -= i & i.wrapping_neg();
+        }
+        s
+    }
+
+    /// [l, r)の総和を求める
+    pub fn range_sum(&self, l: usize, r: usize) -> T {
+        match self.sub {
+            Some(sub) => sub(self.sum(r), self.sum(l)),
+            None => (self.op)(self.sum(r), (self.inv)(self.sum(l))),
+        }
+    }
+}
+
+#[snippet("fenwick-tree")]
+impl<T> FenwickTree<T>
+where
+    T: Copy + PartialOrd,
+{
+    /// [0, i]の総和がx以上となる最小のiを返す. 存在しない場合はnを返す
+    /// 総和が単調増加であること（加算の場合は全要素が非負であること）
+    pub fn lower_bound(&self, x: T) -> usize {
+        let mut pos = 0;
+        let mut acc = self.e;
+        let mut k = if self.n == 0 {
+            0
+        } else {
+            1 << (usize::BITS - 1 - self.n.leading_zeros())
+        };
+        while k > 0 {
+            if pos + k <= self.n {
+                let next = (self.op)(acc, self.data[pos + k]);
+                if next < x {
+                    pos += k;
+                    acc = next;
+                }
+            }
+            k >>= 1;
+        }
+        pos
+    }
+}
+
+#[snippet("range-add-fenwick-tree")]
+#[snippet(include = "fenwick-tree")]
+/// 区間加算・区間和取得ができるFenwick木. インデックスは0-origin
+pub struct RangeAddFenwickTree<T> {
+    // sum(r) = (p0.sum(r) + p1.sum(r) * r) - (m0.sum(r) + m1.sum(r) * r)
+    // 符号なし整数でも負の途中結果を作らないよう、正の項と負の項を分けて持つ
+    p0: FenwickTree<T>,
+    p1: FenwickTree<T>,
+    m0: FenwickTree<T>,
+    m1: FenwickTree<T>,
+}
+
+#[snippet("range-add-fenwick-tree")]
+impl<T> RangeAddFenwickTree<T>
+where
+    T: num::Num + num::FromPrimitive + Copy,
+{
+    pub fn new(n: usize) -> Self {
+        Self {
+            p0: FenwickTree::new(n + 1),
+            p1: FenwickTree::new(n + 1),
+            m0: FenwickTree::new(n + 1),
+            m1: FenwickTree::new(n + 1),
+        }
+    }
+
+    /// [l, r)の各要素にxを加える
+    pub fn add(&mut self, l: usize, r: usize, x: T) {
+        let lt = T::from_usize(l).unwrap();
+        let rt = T::from_usize(r).unwrap();
+        self.m0.add(l, x * lt);
+        self.p0.add(r, x * rt);
+        self.p1.add(l, x);
+        self.m1.add(r, x);
+    }
+
+    /// [0, r)の総和を求める
+    pub fn sum(&self, r: usize) -> T {
+        let rt = T::from_usize(r).unwrap();
+        (self.p0.sum(r) + self.p1.sum(r) * rt) - (self.m0.sum(r) + self.m1.sum(r) * rt)
+    }
+
+    /// [l, r)の総和を求める
+    pub fn range_sum(&self, l: usize, r: usize) -> T {
+        self.sum(r) - self.sum(l)
+    }
+}
+
+#[snippet("fenwick-tree-2d")]
+/// 2次元Fenwick木. 1点加算・長方形領域の和の取得を行う. インデックスは0-origin
+pub struct FenwickTree2D<T> {
+    h: usize,
+    w: usize,
+    // 内部では1-originで管理する
+    data: Vec<Vec<T>>,
+}
+
+#[snippet("fenwick-tree-2d")]
+impl<T> FenwickTree2D<T>
+where
+    T: num::Num + Copy,
+{
+    pub fn new(h: usize, w: usize) -> Self {
+        Self {
+            h,
+            w,
+            data: vec![vec![T::zero(); w + 1]; h + 1],
+        }
+    }
+
+    /// (i, j)の要素にxを加える
+    pub fn add(&mut self, i: usize, j: usize, x: T) {
+        let mut a = i + 1;
+        while a <= self.h {
+            let mut b = j + 1;
+            while b <= self.w {
+                self.data[a][b] = self.data[a][b] + x;
+                b += b & b.wrapping_neg();
+            }
+            a += a & a.wrapping_neg();
+        }
+    }
+
+    /// [0, r) × [0, c)の総和を求める
+    pub fn sum(&self, r: usize, c: usize) -> T {
+        let mut s = T::zero();
+        let mut a = r;
+        while a > 0 {
+            let mut b = c;
+            while b > 0 {
+                s = s + self.data[a][b];
+                b -= b & b.wrapping_neg();
+            }
+            a -= a & a.wrapping_neg();
+        }
+        s
+    }
+
+    /// [r0, r1) × [c0, c1)の総和を求める
+    pub fn rect_sum(&self, r0: usize, r1: usize, c0: usize, c1: usize) -> T {
+        // 符号なし整数でも負の途中結果を作らない順で計算する
+        (self.sum(r1, c1) + self.sum(r0, c0)) - self.sum(r0, c1) - self.sum(r1, c0)
+    }
+}
+
+#[snippet("compressed-fenwick-tree-2d")]
+#[snippet(include = "fenwick-tree")]
+/// 座標圧縮した2次元Fenwick木. 加算を行う点を事前に与えることで、疎で広い座標での長方形領域の和を取得する
+pub struct CompressedFenwickTree2D<T> {
+    // 圧縮後のx座標
+    xs: Vec<i64>,
+    // ys[i]: x方向のFenwick木のノードiが担当する点のy座標
+    ys: Vec<Vec<i64>>,
+    bits: Vec<FenwickTree<T>>,
+}
+
+#[snippet("compressed-fenwick-tree-2d")]
+impl<T> CompressedFenwickTree2D<T>
+where
+    T: num::Num + Copy,
+{
+    /// pointsは加算を行う可能性のある点
+    pub fn new(points: &[(i64, i64)]) -> Self {
+        let mut xs: Vec<i64> = points.iter().map(|p| p.0).collect();
+        xs.sort();
+        xs.dedup();
+        let n = xs.len();
+        let mut ys = vec![vec![]; n + 1];
+        for &(x, y) in points.iter() {
+            let mut i = xs.partition_point(|&v| v < x) + 1;
+            while i <= n {
+                ys[i].push(y);
+                i += i & i.wrapping_neg();
+            }
+        }
+        for y in ys.iter_mut() {
+            y.sort();
+            y.dedup();
+        }
+        let bits = ys.iter().map(|y| FenwickTree::new(y.len())).collect();
+        Self { xs, ys, bits }
+    }
+
+    /// 点(x, y)にwを加える. (x, y)は事前に与えた点であること
+    pub fn add(&mut self, x: i64, y: i64, w: T) {
+        let xi = self.xs.partition_point(|&v| v < x);
+        assert!(xi < self.xs.len() && self.xs[xi] == x, "unknown point");
+        let mut i = xi + 1;
+        while i <= self.xs.len() {
+            let yi = self.ys[i].partition_point(|&v| v < y);
+            assert!(
+                yi < self.ys[i].len() && self.ys[i][yi] == y,
+                "unknown point"
+            );
+            self.bits[i].add(yi, w);
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    // x座標がx未満かつy座標が[y0, y1)の点の総和
+    fn sum(&self, x: i64, y0: i64, y1: i64) -> T {
+        let mut s = T::zero();
+        let mut i = self.xs.partition_point(|&v| v < x);
+        while i > 0 {
+            let l = self.ys[i].partition_point(|&v| v < y0);
+            let r = self.ys[i].partition_point(|&v| v < y1);
+            s = s + (self.bits[i].sum(r) - self.bits[i].sum(l));
+            i -= i & i.wrapping_neg();
+        }
+        s
+    }
+
+    /// [x0, x1) × [y0, y1)の総和を求める
+    pub fn rect_sum(&self, x0: i64, x1: i64, y0: i64, y1: i64) -> T {
+        if x0 >= x1 || y0 >= y1 {
+            return T::zero();
+        }
+        self.sum(x1, y0, y1) - self.sum(x0, y0, y1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fenwick_tree() {
+        let mut ft: FenwickTree<i64> = FenwickTree::new(5);
+        for (i, x) in [1, 2, 3, 4, 5].into_iter().enumerate() {
+            ft.add(i, x);
+        }
+        assert_eq!(ft.sum(0), 0);
+        assert_eq!(ft.sum(3), 6);
+        assert_eq!(ft.sum(5), 15);
+        assert_eq!(ft.range_sum(1, 4), 9);
+        // 負の数
+        ft.add(2, -10);
+        assert_eq!(ft.range_sum(1, 4), -1);
+        assert_eq!(ft.range_sum(3, 3), 0);
+
+        // 符号なし整数
+        let mut ft: FenwickTree<usize> = FenwickTree::new(4);
+        for (i, x) in [5, 0, 7, 2].into_iter().enumerate() {
+            ft.add(i, x);
+        }
+        assert_eq!(ft.range_sum(1, 3), 7);
+        assert_eq!(ft.range_sum(2, 4), 9);
+        assert_eq!(ft.range_sum(4, 4), 0);
+    }
+
+    #[test]
+    fn test_fenwick_tree_with_op() {
+        // xor
+        let mut ft: FenwickTree<u32> = FenwickTree::with_op(4, 0, |a, b| a ^ b, |a| a);
+        for (i, x) in [0b001, 0b010, 0b100, 0b011].into_iter().enumerate() {
+            ft.add(i, x);
+        }
+        assert_eq!(ft.sum(4), 0b100);
+        assert_eq!(ft.range_sum(1, 3), 0b110);
+        assert_eq!(ft.range_sum(1, 4), 0b101);
+    }
+
+    #[test]
+    fn test_fenwick_tree_lower_bound() {
+        let mut ft: FenwickTree<u64> = FenwickTree::new(6);
+        for (i, x) in [1, 0, 2, 3, 0, 4].into_iter().enumerate() {
+            ft.add(i, x);
+        }
+        // 累積和: 1, 1, 3, 6, 6, 10
+        assert_eq!(ft.lower_bound(0), 0);
+        assert_eq!(ft.lower_bound(1), 0);
+        assert_eq!(ft.lower_bound(2), 2);
+        assert_eq!(ft.lower_bound(6), 3);
+        assert_eq!(ft.lower_bound(7), 5);
+        assert_eq!(ft.lower_bound(10), 5);
+        // 総和より大きい
+        assert_eq!(ft.lower_bound(11), 6);
+        // 空
+        let ft: FenwickTree<u64> = FenwickTree::new(0);
+        assert_eq!(ft.lower_bound(1), 0);
+    }
+
+    #[test]
+    fn test_range_add_fenwick_tree() {
+        let mut ft: RangeAddFenwickTree<i64> = RangeAddFenwickTree::new(5);
+        ft.add(0, 3, 2);
+        ft.add(2, 5, -1);
+        // 2, 2, 1, -1, -1
+        assert_eq!(ft.sum(5), 3);
+        assert_eq!(ft.range_sum(0, 2), 4);
+        assert_eq!(ft.range_sum(2, 3), 1);
+        assert_eq!(ft.range_sum(1, 4), 2);
+        assert_eq!(ft.range_sum(4, 4), 0);
+
+        // 符号なし整数
+        let mut ft: RangeAddFenwickTree<u64> = RangeAddFenwickTree::new(5);
+        ft.add(1, 4, 3);
+        ft.add(0, 2, 5);
+        // 5, 8, 3, 3, 0
+        assert_eq!(ft.sum(5), 19);
+        assert_eq!(ft.range_sum(1, 3), 11);
+        assert_eq!(ft.range_sum(3, 5), 3);
+        assert_eq!(ft.range_sum(4, 5), 0);
+    }
+
+    #[test]
+    fn test_fenwick_tree_2d() {
+        let mut ft: FenwickTree2D<i64> = FenwickTree2D::new(3, 4);
+        let mut grid = vec![vec![0i64; 4]; 3];
+        for (i, j, x) in [(0, 0, 1), (1, 2, 5), (2, 3, -2), (1, 2, 3), (2, 0, 4)] {
+            ft.add(i, j, x);
+            grid[i][j] += x;
+        }
+        // 全探索と一致すること
+        for r0 in 0..=3 {
+            for r1 in r0..=3 {
+                for c0 in 0..=4 {
+                    for c1 in c0..=4 {
+                        let expected: i64 = grid[r0..r1]
+                            .iter()
+                            .map(|row| row[c0..c1].iter().sum::<i64>())
+                            .sum();
+                        assert_eq!(ft.rect_sum(r0, r1, c0, c1), expected);
+                    }
+                }
+            }
+        }
+        assert_eq!(ft.sum(3, 4), 11);
+
+        // 符号なし整数
+        let mut ft: FenwickTree2D<usize> = FenwickTree2D::new(2, 2);
+        ft.add(0, 0, 10);
+        ft.add(1, 1, 1);
+        assert_eq!(ft.rect_sum(1, 2, 1, 2), 1);
+        assert_eq!(ft.rect_sum(0, 2, 1, 2), 1);
+        assert_eq!(ft.rect_sum(0, 1, 0, 2), 10);
+    }
+
+    #[test]
+    fn test_compressed_fenwick_tree_2d() {
+        let points: Vec<(i64, i64)> = vec![
+            (0, 0),
+            (1_000_000_000, -1_000_000_000),
+            (5, 7),
+            (5, -3),
+            (-8, 7),
+            (1_000_000_000, 1_000_000_000),
+        ];
+        let mut ft: CompressedFenwickTree2D<i64> = CompressedFenwickTree2D::new(&points);
+        // 点の個数を数える
+        for &(x, y) in points.iter() {
+            ft.add(x, y, 1);
+        }
+        assert_eq!(ft.rect_sum(-10, 10, -10, 10), 4);
+        assert_eq!(ft.rect_sum(5, 6, -10, 10), 2);
+        assert_eq!(ft.rect_sum(0, 1_000_000_001, 0, 1_000_000_001), 3);
+        assert_eq!(ft.rect_sum(i64::MIN, i64::MAX, i64::MIN, i64::MAX), 6);
+        // 空の領域
+        assert_eq!(ft.rect_sum(6, 5, -10, 10), 0);
+        assert_eq!(ft.rect_sum(1, 5, -10, 10), 0);
+
+        // 重みつき
+        ft.add(5, 7, 10);
+        ft.add(-8, 7, -3);
+        assert_eq!(ft.rect_sum(-10, 10, 7, 8), 9);
+
+        // 符号なし整数
+        let mut ft: CompressedFenwickTree2D<usize> = CompressedFenwickTree2D::new(&points);
+        ft.add(0, 0, 10);
+        ft.add(5, 7, 1);
+        assert_eq!(ft.rect_sum(5, 6, 7, 8), 1);
+        assert_eq!(ft.rect_sum(1, 10, 1, 10), 1);
+        assert_eq!(ft.rect_sum(-10, 10, -10, 10), 11);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_compressed_fenwick_tree_2d_panic() {
+        // 事前に与えていない点
+        let mut ft: CompressedFenwickTree2D<i64> = CompressedFenwickTree2D::new(&[(0, 0), (1, 1)]);
+        ft.add(0, 1, 1);
+    }
+}
@@ -0,0 +1,593 @@
+use crate::math::gcd;
+use cargo_snippet::snippet;
+
+#[snippet("point")]
+/// 浮動小数点数の比較に用いる許容誤差
+pub const EPS: f64 = 1e-9;
+
+#[snippet("point")]
+/// 2次元平面上の点（ベクトル）
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[snippet("point")]
+impl Point {
+    pub fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+
+    /// 内積
+    pub fn dot(self, other: Point) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// 外積（z成分）
+    pub fn cross(self, other: Point) -> f64 {
+        self.x * other.y - self.y * other.x
+    }
+
+    /// ノルム（長さの2乗）
+    pub fn norm(self) -> f64 {
+        self.dot(self)
+    }
+
+    /// 長さ
+    pub fn abs(self) -> f64 {
+        self.norm().sqrt()
+    }
+
+    /// 反時計回りに90度回転させる
+    pub fn rot90(self) -> Point {
+        Point::new(-self.y, self.x)
+    }
+}
+
+#[snippet("point")]
+impl std::ops::Add for Point {
+    type Output = Point;
+    fn add(self, other: Point) -> Point {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+#[snippet("point")]
+impl std::ops::Sub for Point {
+    type Output = Point;
+    fn sub(self, other: Point) -> Point {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+#[snippet("point")]
+impl std::ops::Mul<f64> for Point {
+    type Output = Point;
+    fn mul(self, k: f64) -> Point {
+        Point::new(self.x * k, self.y * k)
+    }
+}
+
+#[snippet("point")]
+impl std::ops::Div<f64> for Point {
+    type Output = Point;
+    fn div(self, k: f64) -> Point {
+        Point::new(self.x / k, self.y / k)
+    }
+}
+
+#[snippet("line")]
+#[snippet(include = "point")]
+/// 点a, bを通る直線. 半平面として扱う場合はaからbに向かって左側を表す
+#[derive(Clone, Copy, Debug)]
+pub struct Line {
+    pub a: Point,
+    pub b: Point,
+}
+
+#[snippet("line")]
+impl Line {
+    pub fn new(a: Point, b: Point) -> Self {
+        Self { a, b }
+    }
+
+    /// 方向ベクトル
+    pub fn dir(&self) -> Point {
+        self.b - self.a
+    }
+
+    /// 点pを直線に射影した点
+    pub fn projection(&self, p: Point) -> Point {
+        let d = self.dir();
+        self.a + d * ((p - self.a).dot(d) / d.norm())
+    }
+
+    /// 2直線の交点. 平行でないこと
+    pub fn cross_point(&self, other: &Line) -> Point {
+        let d = self.dir().cross(other.dir());
+        let t = (other.a - self.a).cross(other.dir()) / d;
+        self.a + self.dir() * t
+    }
+}
+
+#[snippet("circle")]
+#[snippet(include = "line")]
+/// 中心c, 半径rの円
+#[derive(Clone, Copy, Debug)]
+pub struct Circle {
+    pub c: Point,
+    pub r: f64,
+}
+
+#[snippet("circle")]
+impl Circle {
+    pub fn new(c: Point, r: f64) -> Self {
+        Self { c, r }
+    }
+
+    /// 点pが円の内部（周上を含む）にあるか
+    pub fn contains(&self, p: Point) -> bool {
+        (p - self.c).abs() <= self.r + EPS
+    }
+
+    /// 円と直線の交点. 接する場合は1点を返す
+    pub fn cross_points_line(&self, l: &Line) -> Vec<Point> {
+        let h = l.projection(self.c);
+        let d = (h - self.c).abs();
+        if d > self.r + EPS {
+            return vec![];
+        }
+        if (d - self.r).abs() < EPS {
+            return vec![h];
+        }
+        let e = l.dir() / l.dir().abs();
+        let base = (self.r * self.r - d * d).sqrt();
+        vec![h - e * base, h + e * base]
+    }
+
+    /// 2円の交点. 接する場合は1点を返す. 同心円の場合は交点なしとする
+    pub fn cross_points_circle(&self, other: &Circle) -> Vec<Point> {
+        let v = other.c - self.c;
+        let d = v.abs();
+        if d < EPS || d > self.r + other.r + EPS || d < (self.r - other.r).abs() - EPS {
+            return vec![];
+        }
+        // 中心を結ぶ直線上での、交点を結ぶ線分の中点までの距離
+        let a = (self.r * self.r - other.r * other.r + d * d) / (2.0 * d);
+        let h = (self.r * self.r - a * a).max(0.0).sqrt();
+        let base = self.c + v * (a / d);
+        if h < EPS {
+            return vec![base];
+        }
+        let e = v.rot90() / d;
+        vec![base - e * h, base + e * h]
+    }
+}
+
+#[snippet("convex_hull")]
+#[snippet(include = "point")]
+/// 凸包を反時計回りに求める（Andrewのアルゴリズム）. 辺上の点は含まない
+pub fn convex_hull(points: &[Point]) -> Vec<Point> {
+    let mut p = points.to_vec();
+    p.sort_by(|a, b| {
+        a.x.partial_cmp(&b.x)
+            .unwrap()
+            .then(a.y.partial_cmp(&b.y).unwrap())
+    });
+    p.dedup_by(|a, b| (*a - *b).abs() < EPS);
+    if p.len() <= 2 {
+        return p;
+    }
+    let mut hull: Vec<Point> = Vec::with_capacity(2 * p.len());
+    // 下側凸包 → 上側凸包の順に構成する
+    for iter in [p.clone(), p.into_iter().rev().collect::<Vec<_>>()] {
+        let base = hull.len();
+        for q in iter {
+            while hull.len() >= base + 2 {
+                let l = hull.len();
+                if (hull[l - 1] - hull[l - 2]).cross(q - hull[l - 1]) > EPS {
+                    break;
+                }
+                hull.pop();
+            }
+            hull.push(q);
+        }
+        hull.pop();
+    }
+    hull
+}
+
+#[snippet(include = "convex_hull")]
+/// 最遠点対の距離を求める（キャリパー法）
+pub fn farthest_pair(points: &[Point]) -> f64 {
+    let h = convex_hull(points);
+    let n = h.len();
+    if n <= 1 {
+        return 0.0;
+    }
+    if n == 2 {
+        return (h[0] - h[1]).abs();
+    }
+    // x座標が最小の点と最大の点から始めて凸包を1周させる
+    let si = (0..n)
+        .min_by(|&a, &b| h[a].x.partial_cmp(&h[b].x).unwrap())
+        .unwrap();
+    let sj = (0..n)
+        .max_by(|&a, &b| h[a].x.partial_cmp(&h[b].x).unwrap())
+        .unwrap();
+    let (mut i, mut j) = (si, sj);
+    let mut res = (h[i] - h[j]).abs();
+    loop {
+        if (h[(i + 1) % n] - h[i]).cross(h[(j + 1) % n] - h[j]) < 0.0 {
+            i = (i + 1) % n;
+        } else {
+            j = (j + 1) % n;
+        }
+        res = res.max((h[i] - h[j]).abs());
+        if i == si && j == sj {
+            break;
+        }
+    }
+    res
+}
+
+#[snippet("closest_pair")]
+#[snippet(include = "point")]
+/// 最近点対の距離を求める（分割統治法）. 点が2つ未満の場合は無限大を返す
+pub fn closest_pair(points: &[Point]) -> f64 {
+    let mut p = points.to_vec();
+    p.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap());
+    closest_pair_rec(&mut p)
+}
+
+#[snippet("closest_pair")]
+// pはx座標でソート済み. 終了時にはy座標でソートされている
+fn closest_pair_rec(p: &mut [Point]) -> f64 {
+    let n = p.len();
+    if n <= 1 {
+        return f64::INFINITY;
+    }
+    let m = n / 2;
+    let x = p[m].x;
+    let mut d = closest_pair_rec(&mut p[..m]).min(closest_pair_rec(&mut p[m..]));
+    // 左右それぞれy座標でソート済みなので、マージしてy座標順にする
+    p.sort_by(|a, b| a.y.partial_cmp(&b.y).unwrap());
+    // 境界からの距離がd未満の点だけを見る
+    let mut near: Vec<Point> = vec![];
+    for &q in p.iter() {
+        if (q.x - x).abs() >= d {
+            continue;
+        }
+        for &r in near.iter().rev() {
+            if q.y - r.y >= d {
+                break;
+            }
+            d = d.min((q - r).abs());
+        }
+        near.push(q);
+    }
+    d
+}
+
+#[snippet(include = "line")]
+/// 凸多角形を直線lで切断し、lの左側（lの上を含む）の部分を返す
+pub fn convex_cut(polygon: &[Point], l: &Line) -> Vec<Point> {
+    let n = polygon.len();
+    let mut res = vec![];
+    for i in 0..n {
+        let p = polygon[i];
+        let q = polygon[(i + 1) % n];
+        let cp = l.dir().cross(p - l.a);
+        let cq = l.dir().cross(q - l.a);
+        if cp > -EPS {
+            res.push(p);
+        }
+        if (cp > EPS && cq < -EPS) || (cp < -EPS && cq > EPS) {
+            res.push(l.cross_point(&Line::new(p, q)));
+        }
+    }
+    res
+}
+
+#[snippet(include = "line")]
+/// 半平面（各直線の左側）の共通部分を凸多角形として反時計回りに求める.
+/// 共通部分は有界であることを仮定する（必要ならバウンディングボックスを加える）. 空の場合は空のベクタを返す
+pub fn half_plane_intersection(lines: &[Line]) -> Vec<Point> {
+    let angle = |l: &Line| l.dir().y.atan2(l.dir().x);
+    let mut ls = lines.to_vec();
+    ls.sort_by(|a, b| angle(a).partial_cmp(&angle(b)).unwrap());
+    // 同じ向きの半平面は最も内側のものだけを残す
+    let mut sorted: Vec<Line> = vec![];
+    for l in ls {
+        if let Some(last) = sorted.last_mut() {
+            if (angle(last) - angle(&l)).abs() < EPS {
+                if last.dir().cross(l.a - last.a) > 0.0 {
+                    *last = l;
+                }
+                continue;
+            }
+        }
+        sorted.push(l);
+    }
+    let inside = |l: &Line, p: Point| l.dir().cross(p - l.a) > EPS;
+    let mut dq: std::collections::VecDeque<Line> = std::collections::VecDeque::new();
+    for l in sorted {
+        while dq.len() >= 2 && !inside(&l, dq[dq.len() - 1].cross_point(&dq[dq.len() - 2])) {
+            dq.pop_back();
+        }
+        while dq.len() >= 2 && !inside(&l, dq[0].cross_point(&dq[1])) {
+            dq.pop_front();
+        }
+        if let Some(back) = dq.back() {
+            // 向きが180度以上変わる場合、共通部分は空（または非有界）
+            if back.dir().cross(l.dir()) <= EPS {
+                return vec![];
+            }
+        }
+        dq.push_back(l);
+    }
+    while dq.len() >= 3 && !inside(&dq[0], dq[dq.len() - 1].cross_point(&dq[dq.len() - 2])) {
+        dq.pop_back();
+    }
+    while dq.len() >= 3 && !inside(&dq[dq.len() - 1], dq[0].cross_point(&dq[1])) {
+        dq.pop_front();
+    }
+    let k = dq.len();
+    if k < 3 {
+        return vec![];
+    }
+    (0..k)
+        .map(|i| dq[i].cross_point(&dq[(i + 1) % k]))
+        .collect()
+}
+
+#[snippet(include = "circle")]
+/// 全ての点を含む最小の円を求める（Welzlのアルゴリズム）. pointsは空でないこと
+pub fn min_enclosing_circle(points: &[Point]) -> Circle {
+    let mut p = points.to_vec();
+    // 期待計算量をO(n)にするためにシャッフルする（xorshift）
+    let mut seed: u64 = 88172645463325252;
+    for i in (1..p.len()).rev() {
+        seed ^= seed << 7;
+        seed ^= seed >> 9;
+        p.swap(i, (seed % (i as u64 + 1)) as usize);
+    }
+    let from_two = |a: Point, b: Point| Circle::new((a + b) / 2.0, (a - b).abs() / 2.0);
+    let from_three = |a: Point, b: Point, c: Point| {
+        let (u, v) = (b - a, c - a);
+        let d = 2.0 * u.cross(v);
+        if d.abs() < EPS {
+            // 3点が同一直線上にある場合は最も離れた2点を直径とする
+            return [from_two(a, b), from_two(b, c), from_two(c, a)]
+                .into_iter()
+                .max_by(|x, y| x.r.partial_cmp(&y.r).unwrap())
+                .unwrap();
+        }
+        let o = Point::new(
+            v.y * u.norm() - u.y * v.norm(),
+            u.x * v.norm() - v.x * u.norm(),
+        ) / d;
+        Circle::new(a + o, o.abs())
+    };
+    let mut c = Circle::new(p[0], 0.0);
+    for i in 0..p.len() {
+        if c.contains(p[i]) {
+            continue;
+        }
+        c = Circle::new(p[i], 0.0);
+        for j in 0..i {
+            if c.contains(p[j]) {
+                continue;
+            }
+            c = from_two(p[i], p[j]);
+            for k in 0..j {
+                if c.contains(p[k]) {
+                    continue;
+                }
+                c = from_three(p[i], p[j], p[k]);
+            }
+        }
+    }
+    c
+}
+
+#[snippet(include = "gcd")]
+/// 格子点p, qを結ぶ線分上の格子点の数（端点を含む）
+pub fn lattice_points_on_segment(p: (i64, i64), q: (i64, i64)) -> u64 {
+    gcd((p.0 - q.0).unsigned_abs(), (p.1 - q.1).unsigned_abs()) + 1
+}
+
+#[snippet(include = "gcd")]
+/// 格子点を頂点とする多角形の周上にある格子点の数
+pub fn lattice_points_on_boundary(polygon: &[(i64, i64)]) -> u64 {
+    let n = polygon.len();
+    (0..n)
+        .map(|i| {
+            let (p, q) = (polygon[i], polygon[(i + 1) % n]);
+            gcd((p.0 - q.0).unsigned_abs(), (p.1 - q.1).unsigned_abs())
+        })
+        .sum()
+}
+
+#[snippet(include = "lattice_points_on_boundary")]
+/// 格子点を頂点とする多角形の内部（周上を含まない）にある格子点の数（Pickの定理）
+pub fn lattice_points_inside(polygon: &[(i64, i64)]) -> u64 {
+    let n = polygon.len();
+    // 面積の2倍
+    let s2 = (0..n)
+        .map(|i| {
+            let (p, q) = (polygon[i], polygon[(i + 1) % n]);
+            p.0 * q.1 - p.1 * q.0
+        })
+        .sum::<i64>()
+        .unsigned_abs();
+    // S = I + B / 2 - 1
+    (s2 + 2 - lattice_points_on_boundary(polygon)) / 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pts(v: &[(f64, f64)]) -> Vec<Point> {
+        v.iter().map(|&(x, y)| Point::new(x, y)).collect()
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-6, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_convex_hull() {
+        // 内部の点と辺上の点は除かれること
+        let p = pts(&[
+            (0.0, 0.0),
+            (2.0, 0.0),
+            (1.0, 1.0),
+            (2.0, 2.0),
+            (0.0, 2.0),
+            (1.0, 0.0),
+        ]);
+        let expected = pts(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]);
+        assert_eq!(convex_hull(&p), expected);
+        // 要素数1
+        assert_eq!(convex_hull(&pts(&[(1.0, 1.0)])), pts(&[(1.0, 1.0)]));
+    }
+
+    #[test]
+    fn test_farthest_pair() {
+        let p = pts(&[(0.0, 0.0), (3.0, 0.0), (3.0, 4.0), (0.0, 4.0), (1.0, 1.0)]);
+        assert_close(farthest_pair(&p), 5.0);
+        // 要素数2
+        assert_close(farthest_pair(&pts(&[(0.0, 0.0), (1.0, 1.0)])), 2f64.sqrt());
+        // 要素数1
+        assert_close(farthest_pair(&pts(&[(1.0, 1.0)])), 0.0);
+    }
+
+    #[test]
+    fn test_closest_pair() {
+        let p = pts(&[(0.0, 0.0), (5.0, 5.0), (1.0, 9.0), (5.0, 6.5), (9.0, 1.0)]);
+        assert_close(closest_pair(&p), 1.5);
+        // 全探索と一致すること
+        let p: Vec<Point> = (0..50)
+            .map(|i| Point::new(((i * 37) % 101) as f64, ((i * 53) % 97) as f64))
+            .collect();
+        let mut expected = f64::INFINITY;
+        for i in 0..p.len() {
+            for j in (i + 1)..p.len() {
+                expected = expected.min((p[i] - p[j]).abs());
+            }
+        }
+        assert_close(closest_pair(&p), expected);
+        // 要素数1
+        assert_eq!(closest_pair(&pts(&[(0.0, 0.0)])), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_convex_cut() {
+        let square = pts(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]);
+        // x = 1 の左側
+        let l = Line::new(Point::new(1.0, 0.0), Point::new(1.0, 2.0));
+        let expected = pts(&[(0.0, 0.0), (1.0, 0.0), (1.0, 2.0), (0.0, 2.0)]);
+        assert_eq!(convex_cut(&square, &l), expected);
+
+        // 座標が小さくても交点を落とさないこと
+        let square = pts(&[(0.0, 0.0), (0.002, 0.0), (0.002, 0.002), (0.0, 0.002)]);
+        let l = Line::new(Point::new(0.001, 0.0), Point::new(0.001, 0.002));
+        let expected = pts(&[(0.0, 0.0), (0.001, 0.0), (0.001, 0.002), (0.0, 0.002)]);
+        assert_eq!(convex_cut(&square, &l), expected);
+    }
+
+    #[test]
+    fn test_half_plane_intersection() {
+        let l = |ax, ay, bx, by| Line::new(Point::new(ax, ay), Point::new(bx, by));
+        // 正方形 [0, 2] x [0, 2] と x + y <= 3
+        let lines = vec![
+            l(0.0, 0.0, 1.0, 0.0),
+            l(2.0, 0.0, 2.0, 1.0),
+            l(2.0, 2.0, 1.0, 2.0),
+            l(0.0, 2.0, 0.0, 1.0),
+            l(3.0, 0.0, 0.0, 3.0),
+            // 冗長な半平面
+            l(0.0, -1.0, 1.0, -1.0),
+        ];
+        let res = half_plane_intersection(&lines);
+        assert_eq!(res.len(), 5);
+        let area: f64 = (0..res.len())
+            .map(|i| res[i].cross(res[(i + 1) % res.len()]))
+            .sum::<f64>()
+            / 2.0;
+        assert_close(area, 3.5);
+        // 共通部分が空
+        let lines = vec![
+            l(0.0, 0.0, 1.0, 0.0),
+            l(0.0, 1.0, 0.0, 0.0),
+            l(1.0, -1.0, 0.0, -1.0),
+        ];
+        assert!(half_plane_intersection(&lines).is_empty());
+    }
+
+    #[test]
+    fn test_cross_points_line() {
+        let c = Circle::new(Point::new(0.0, 0.0), 1.0);
+        let res = c.cross_points_line(&Line::new(Point::new(-2.0, 0.0), Point::new(2.0, 0.0)));
+        assert_eq!(res, pts(&[(-1.0, 0.0), (1.0, 0.0)]));
+        // 接する
+        let res = c.cross_points_line(&Line::new(Point::new(-2.0, 1.0), Point::new(2.0, 1.0)));
+        assert_eq!(res, pts(&[(0.0, 1.0)]));
+        // 交わらない
+        let res = c.cross_points_line(&Line::new(Point::new(-2.0, 2.0), Point::new(2.0, 2.0)));
+        assert!(res.is_empty());
+    }
+
+    #[test]
+    fn test_cross_points_circle() {
+        let c1 = Circle::new(Point::new(0.0, 0.0), 2.0);
+        let res = c1.cross_points_circle(&Circle::new(Point::new(2.0, 0.0), 2.0));
+        assert_eq!(res.len(), 2);
+        assert_close(res[0].x, 1.0);
+        assert_close(res[0].y, -3f64.sqrt());
+        assert_close(res[1].x, 1.0);
+        assert_close(res[1].y, 3f64.sqrt());
+        // 外接する
+        let res = c1.cross_points_circle(&Circle::new(Point::new(3.0, 0.0), 1.0));
+        assert_eq!(res, pts(&[(2.0, 0.0)]));
+        // 内包する
+        let res = c1.cross_points_circle(&Circle::new(Point::new(0.5, 0.0), 1.0));
+        assert!(res.is_empty());
+        // 同心円
+        let res = c1.cross_points_circle(&Circle::new(Point::new(0.0, 0.0), 2.0));
+        assert!(res.is_empty());
+    }
+
+    #[test]
+    fn test_min_enclosing_circle() {
+        let p = pts(&[(0.0, 0.0), (2.0, 0.0), (1.0, 0.5)]);
+        let c = min_enclosing_circle(&p);
+        assert_close(c.c.x, 1.0);
+        assert_close(c.c.y, 0.0);
+        assert_close(c.r, 1.0);
+        // 正三角形の外接円
+        let p = pts(&[(0.0, 0.0), (2.0, 0.0), (1.0, 3f64.sqrt()), (1.0, 0.5)]);
+        let c = min_enclosing_circle(&p);
+        assert_close(c.r, 2.0 / 3f64.sqrt());
+        assert!(p.iter().all(|&q| c.contains(q)));
+        // 要素数1
+        assert_close(min_enclosing_circle(&pts(&[(1.0, 1.0)])).r, 0.0);
+    }
+
+    #[test]
+    fn test_lattice_points() {
+        assert_eq!(lattice_points_on_segment((0, 0), (4, 6)), 3);
+        assert_eq!(lattice_points_on_segment((1, 1), (1, 1)), 1);
+        assert_eq!(lattice_points_on_segment((-3, 0), (3, 0)), 7);
+        let polygon = vec![(0, 0), (4, 0), (4, 3), (0, 3)];
+        assert_eq!(lattice_points_on_boundary(&polygon), 14);
+        assert_eq!(lattice_points_inside(&polygon), 6);
+        // 時計回り
+        let polygon = vec![(0, 0), (0, 2), (2, 0)];
+        assert_eq!(lattice_points_on_boundary(&polygon), 6);
+        assert_eq!(lattice_points_inside(&polygon), 0);
+    }
+}
@@ -0,0 +1,362 @@
+use cargo_snippet::snippet;
+
+#[snippet("min-heap")]
+/// 最小値を取り出す優先度付きキュー
+pub struct MinHeap<T>
+where
+    T: Ord,
+{
+    heap: std::collections::BinaryHeap<std::cmp::Reverse<T>>,
+}
+
+#[snippet("min-heap")]
+impl<T> MinHeap<T>
+where
+    T: Ord,
+{
+    pub fn new() -> Self {
+        Self {
+            heap: std::collections::BinaryHeap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn push(&mut self, x: T) {
+        self.heap.push(std::cmp::Reverse(x));
+    }
+
+    /// 最小値を取り出す
+    pub fn pop(&mut self) -> Option<T> {
+        self.heap.pop().map(|x| x.0)
+    }
+
+    /// 最小値
+    pub fn peek(&self) -> Option<&T> {
+        self.heap.peek().map(|x| &x.0)
+    }
+}
+
+#[snippet("min-heap")]
+impl<T> Default for MinHeap<T>
+where
+    T: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[snippet("erasable-heap")]
+#[snippet(include = "min-heap")]
+/// 任意の要素を削除できる、最小値を取り出す優先度付きキュー
+/// 削除した要素を別のヒープに持ち、最小値が一致したときに両方から取り除く
+pub struct ErasableHeap<T>
+where
+    T: Ord,
+{
+    heap: MinHeap<T>,
+    // 削除予定の要素
+    erased: MinHeap<T>,
+}
+
+#[snippet("erasable-heap")]
+impl<T> ErasableHeap<T>
+where
+    T: Ord,
+{
+    pub fn new() -> Self {
+        Self {
+            heap: MinHeap::new(),
+            erased: MinHeap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len() - self.erased.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // 先頭の削除済みの要素を取り除く
+    fn normalize(&mut self) {
+        while let (Some(x), Some(y)) = (self.heap.peek(), self.erased.peek()) {
+            if x != y {
+                break;
+            }
+            self.heap.pop();
+            self.erased.pop();
+        }
+    }
+
+    pub fn push(&mut self, x: T) {
+        self.heap.push(x);
+        self.normalize();
+    }
+
+    /// xを1つ削除する. xはヒープに含まれていること
+    pub fn remove(&mut self, x: T) {
+        self.erased.push(x);
+        self.normalize();
+    }
+
+    /// 最小値を取り出す
+    pub fn pop(&mut self) -> Option<T> {
+        let res = self.heap.pop();
+        self.normalize();
+        res
+    }
+
+    /// 最小値
+    pub fn peek(&self) -> Option<&T> {
+        self.heap.peek()
+    }
+}
+
+#[snippet("erasable-heap")]
+impl<T> Default for ErasableHeap<T>
+where
+    T: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[snippet("leftist-heap")]
+struct LeftistNode<T> {
+    val: T,
+    // 右の子を辿って空に至るまでの距離
+    rank: usize,
+    left: Option<Box<LeftistNode<T>>>,
+    right: Option<Box<LeftistNode<T>>>,
+}
+
+#[snippet("leftist-heap")]
+/// 併合可能な最小値を取り出す優先度付きキュー（Leftist Heap）. 併合をO(log n)で行う
+pub struct LeftistHeap<T>
+where
+    T: Ord,
+{
+    root: Option<Box<LeftistNode<T>>>,
+    len: usize,
+}
+
+#[snippet("leftist-heap")]
+impl<T> LeftistHeap<T>
+where
+    T: Ord,
+{
+    pub fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn rank(t: &Option<Box<LeftistNode<T>>>) -> usize {
+        t.as_ref().map_or(0, |t| t.rank)
+    }
+
+    fn meld_nodes(
+        a: Option<Box<LeftistNode<T>>>,
+        b: Option<Box<LeftistNode<T>>>,
+    ) -> Option<Box<LeftistNode<T>>> {
+        match (a, b) {
+            (None, t) | (t, None) => t,
+            (Some(mut a), Some(mut b)) => {
+                if a.val > b.val {
+                    std::mem::swap(&mut a, &mut b);
+                }
+                // 右の子に併合し、左の子の方がrankが小さくならないようにする
+                a.right = Self::meld_nodes(a.right.take(), Some(b));
+                if Self::rank(&a.left) < Self::rank(&a.right) {
+                    std::mem::swap(&mut a.left, &mut a.right);
+                }
+                a.rank = Self::rank(&a.right) + 1;
+                Some(a)
+            }
+        }
+    }
+
+    pub fn push(&mut self, x: T) {
+        let node = Box::new(LeftistNode {
+            val: x,
+            rank: 1,
+            left: None,
+            right: None,
+        });
+        self.root = Self::meld_nodes(self.root.take(), Some(node));
+        self.len += 1;
+    }
+
+    /// 最小値を取り出す
+    pub fn pop(&mut self) -> Option<T> {
+        let root = self.root.take()?;
+        let LeftistNode {
+            val, left, right, ..
+        } = *root;
+        self.root = Self::meld_nodes(left, right);
+        self.len -= 1;
+        Some(val)
+    }
+
+    /// 最小値
+    pub fn peek(&self) -> Option<&T> {
+        self.root.as_ref().map(|t| &t.val)
+    }
+
+    /// otherの要素を全て移す
+    pub fn meld(&mut self, mut other: Self) {
+        self.root = Self::meld_nodes(self.root.take(), other.root.take());
+        self.len += other.len;
+    }
+}
+
+#[snippet("leftist-heap")]
+impl<T> Default for LeftistHeap<T>
+where
+    T: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[snippet("leftist-heap")]
+impl<T> Drop for LeftistHeap<T>
+where
+    T: Ord,
+{
+    // 左の子を辿る経路は長くなりうるので、再帰せずに解放する
+    fn drop(&mut self) {
+        let mut stack: Vec<Box<LeftistNode<T>>> = self.root.take().into_iter().collect();
+        while let Some(mut t) = stack.pop() {
+            stack.extend(t.left.take());
+            stack.extend(t.right.take());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+
+    #[test]
+    fn test_min_heap() {
+        let mut heap = MinHeap::new();
+        assert_eq!(heap.pop(), None);
+        for &x in [3, 1, 4, 1, 5].iter() {
+            heap.push(x);
+        }
+        assert_eq!(heap.len(), 5);
+        assert_eq!(heap.peek(), Some(&1));
+        let v: Vec<i32> = std::iter::from_fn(|| heap.pop()).collect();
+        assert_eq!(v, vec![1, 1, 3, 4, 5]);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn test_erasable_heap() {
+        let mut heap = ErasableHeap::new();
+        for &x in [5, 3, 8, 3, 1].iter() {
+            heap.push(x);
+        }
+        heap.remove(3);
+        heap.remove(1);
+        assert_eq!(heap.len(), 3);
+        assert_eq!(heap.peek(), Some(&3));
+        heap.remove(3);
+        assert_eq!(heap.pop(), Some(5));
+        assert_eq!(heap.pop(), Some(8));
+        assert_eq!(heap.pop(), None);
+
+        // 全探索と一致すること
+        let mut rng = Rng::default();
+        let mut heap = ErasableHeap::new();
+        let mut v: Vec<u64> = vec![];
+        for _ in 0..1000 {
+            match rng.next_below(3) {
+                0 if !v.is_empty() => {
+                    let i = rng.next_usize(v.len());
+                    heap.remove(v.swap_remove(i));
+                }
+                1 => {
+                    let min = v.iter().copied().min();
+                    if let Some(m) = min {
+                        let i = v.iter().position(|&y| y == m).unwrap();
+                        v.swap_remove(i);
+                    }
+                    assert_eq!(heap.pop(), min);
+                }
+                _ => {
+                    let y = rng.next_below(20);
+                    heap.push(y);
+                    v.push(y);
+                }
+            }
+            assert_eq!(heap.len(), v.len());
+            assert_eq!(heap.peek(), v.iter().min());
+        }
+    }
+
+    #[test]
+    fn test_leftist_heap() {
+        let mut a = LeftistHeap::new();
+        let mut b = LeftistHeap::new();
+        for &x in [9, 2, 7].iter() {
+            a.push(x);
+        }
+        for &x in [4, 2, 8, 1].iter() {
+            b.push(x);
+        }
+        assert_eq!(a.peek(), Some(&2));
+        a.meld(b);
+        assert_eq!(a.len(), 7);
+        let v: Vec<i32> = std::iter::from_fn(|| a.pop()).collect();
+        assert_eq!(v, vec![1, 2, 2, 4, 7, 8, 9]);
+        assert!(a.is_empty());
+
+        // 多数のヒープを併合する
+        let mut heaps: Vec<LeftistHeap<usize>> = (0..1000)
+            .map(|i| {
+                let mut h = LeftistHeap::new();
+                h.push((i * 7919) % 1000);
+                h
+            })
+            .collect();
+        while heaps.len() > 1 {
+            let h = heaps.pop().unwrap();
+            let i = heaps.len() / 2;
+            heaps[i].meld(h);
+        }
+        let mut h = heaps.pop().unwrap();
+        let v: Vec<usize> = std::iter::from_fn(|| h.pop()).collect();
+        assert_eq!(v, (0..1000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_leftist_heap_drop_long_spine() {
+        // 降順に追加すると左の子を辿る経路の長さがO(n)になる
+        let mut h = LeftistHeap::new();
+        for x in (0..1_000_000).rev() {
+            h.push(x);
+        }
+        assert_eq!(h.peek(), Some(&0));
+        drop(h);
+    }
+}
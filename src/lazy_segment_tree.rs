@@ -0,0 +1,539 @@
+use crate::cumulative_sum::{range_to_pair, Max, Min, Monoid};
+use cargo_snippet::snippet;
+
+#[snippet("map-monoid")]
+#[snippet(include = "monoid")]
+/// モノイドMの要素に作用する写像の集合. 写像の合成についてモノイドをなすこと
+pub trait MapMonoid<M>
+where
+    M: Monoid,
+{
+    type F: Clone;
+    /// 恒等写像
+    fn identity_map() -> Self::F;
+    /// fをxに作用させる
+    fn mapping(f: &Self::F, x: &M::S) -> M::S;
+    /// gを作用させた後にfを作用させる写像
+    fn composition(f: &Self::F, g: &Self::F) -> Self::F;
+}
+
+#[snippet("lazy-segment-tree")]
+#[snippet(include = "map-monoid")]
+#[snippet(include = "range_to_pair")]
+/// 遅延評価セグメント木. 区間作用・区間取得を行う. インデックスは0-origin
+pub struct LazySegTree<M, F>
+where
+    M: Monoid,
+    F: MapMonoid<M>,
+{
+    n: usize,
+    // 葉の数（n以上の最小の2冪）
+    size: usize,
+    log: u32,
+    // 1-originの完全二分木. 葉はdata[size..]
+    data: Vec<M::S>,
+    // 子に伝播していない写像
+    lazy: Vec<F::F>,
+}
+
+#[snippet("lazy-segment-tree")]
+impl<M, F> LazySegTree<M, F>
+where
+    M: Monoid,
+    F: MapMonoid<M>,
+{
+    pub fn new(n: usize) -> Self {
+        Self::from_vec(vec![M::identity(); n])
+    }
+
+    pub fn from_vec(v: Vec<M::S>) -> Self {
+        let n = v.len();
+        let size = n.next_power_of_two();
+        let log = size.trailing_zeros();
+        let mut data = vec![M::identity(); 2 * size];
+        for (i, x) in v.into_iter().enumerate() {
+            data[size + i] = x;
+        }
+        let lazy = vec![F::identity_map(); size];
+        let mut st = Self {
+            n,
+            size,
+            log,
+            data,
+            lazy,
+        };
+        for i in (1..size).rev() {
+            st.update(i);
+        }
+        st
+    }
+
+    fn update(&mut self, k: usize) {
+        self.data[k] = M::op(&self.data[2 * k], &self.data[2 * k + 1]);
+    }
+
+    fn all_apply(&mut self, k: usize, f: &F::F) {
+        self.data[k] = F::mapping(f, &self.data[k]);
+        if k < self.size {
+            self.lazy[k] = F::composition(f, &self.lazy[k]);
+        }
+    }
+
+    fn push(&mut self, k: usize) {
+        let f = std::mem::replace(&mut self.lazy[k], F::identity_map());
+        self.all_apply(2 * k, &f);
+        self.all_apply(2 * k + 1, &f);
+    }
+
+    /// p番目の要素をxにする
+    pub fn set(&mut self, p: usize, x: M::S) {
+        assert!(p < self.n);
+        let p = p + self.size;
+        for i in (1..=self.log).rev() {
+            self.push(p >> i);
+        }
+        self.data[p] = x;
+        for i in 1..=self.log {
+            self.update(p >> i);
+        }
+    }
+
+    /// p番目の要素を取得する
+    pub fn get(&mut self, p: usize) -> M::S {
+        assert!(p < self.n);
+        let p = p + self.size;
+        for i in (1..=self.log).rev() {
+            self.push(p >> i);
+        }
+        self.data[p].clone()
+    }
+
+    /// 範囲内の要素の総積を求める
+    pub fn prod<R>(&mut self, range: R) -> M::S
+    where
+        R: std::ops::RangeBounds<usize>,
+    {
+        let (l, r) = range_to_pair(range, self.n);
+        if l == r {
+            return M::identity();
+        }
+        let mut l = l + self.size;
+        let mut r = r + self.size;
+        for i in (1..=self.log).rev() {
+            if ((l >> i) << i) != l {
+                self.push(l >> i);
+            }
+            if ((r >> i) << i) != r {
+                self.push((r - 1) >> i);
+            }
+        }
+        let mut sml = M::identity();
+        let mut smr = M::identity();
+        while l < r {
+            if l & 1 == 1 {
+                sml = M::op(&sml, &self.data[l]);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                smr = M::op(&self.data[r], &smr);
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+        M::op(&sml, &smr)
+    }
+
+    /// 全要素の総積を求める
+    pub fn all_prod(&self) -> M::S {
+        self.data[1].clone()
+    }
+
+    /// 範囲内の各要素に写像fを作用させる
+    pub fn apply<R>(&mut self, range: R, f: F::F)
+    where
+        R: std::ops::RangeBounds<usize>,
+    {
+        let (l, r) = range_to_pair(range, self.n);
+        if l == r {
+            return;
+        }
+        let l = l + self.size;
+        let r = r + self.size;
+        for i in (1..=self.log).rev() {
+            if ((l >> i) << i) != l {
+                self.push(l >> i);
+            }
+            if ((r >> i) << i) != r {
+                self.push((r - 1) >> i);
+            }
+        }
+        {
+            let mut l = l;
+            let mut r = r;
+            while l < r {
+                if l & 1 == 1 {
+                    self.all_apply(l, &f);
+                    l += 1;
+                }
+                if r & 1 == 1 {
+                    r -= 1;
+                    self.all_apply(r, &f);
+                }
+                l >>= 1;
+                r >>= 1;
+            }
+        }
+        for i in 1..=self.log {
+            if ((l >> i) << i) != l {
+                self.update(l >> i);
+            }
+            if ((r >> i) << i) != r {
+                self.update((r - 1) >> i);
+            }
+        }
+    }
+
+    /// g(prod(l..r)) = true となる最大のrを返す
+    /// gは単調（trueとなる区間の部分区間もtrue）で、g(単位元) = true であること
+    pub fn max_right<G>(&mut self, l: usize, g: G) -> usize
+    where
+        G: Fn(&M::S) -> bool,
+    {
+        assert!(l <= self.n);
+        assert!(g(&M::identity()));
+        if l == self.n {
+            return self.n;
+        }
+        let mut l = l + self.size;
+        for i in (1..=self.log).rev() {
+            self.push(l >> i);
+        }
+        let mut sm = M::identity();
+        loop {
+            while l & 1 == 0 {
+                l >>= 1;
+            }
+            if !g(&M::op(&sm, &self.data[l])) {
+                // 条件を満たさなくなる葉まで降りる
+                while l < self.size {
+                    self.push(l);
+                    l *= 2;
+                    let res = M::op(&sm, &self.data[l]);
+                    if g(&res) {
+                        sm = res;
+                        l += 1;
+                    }
+                }
+                return l - self.size;
+            }
+            sm = M::op(&sm, &self.data[l]);
+            l += 1;
+            if l & l.wrapping_neg() == l {
+                return self.n;
+            }
+        }
+    }
+
+    /// g(prod(l..r)) = true となる最小のlを返す
+    /// gは単調（trueとなる区間の部分区間もtrue）で、g(単位元) = true であること
+    pub fn min_left<G>(&mut self, r: usize, g: G) -> usize
+    where
+        G: Fn(&M::S) -> bool,
+    {
+        assert!(r <= self.n);
+        assert!(g(&M::identity()));
+        if r == 0 {
+            return 0;
+        }
+        let mut r = r + self.size;
+        for i in (1..=self.log).rev() {
+            self.push((r - 1) >> i);
+        }
+        let mut sm = M::identity();
+        loop {
+            r -= 1;
+            while r > 1 && r & 1 == 1 {
+                r >>= 1;
+            }
+            if !g(&M::op(&self.data[r], &sm)) {
+                // 条件を満たさなくなる葉まで降りる
+                while r < self.size {
+                    self.push(r);
+                    r = 2 * r + 1;
+                    let res = M::op(&self.data[r], &sm);
+                    if g(&res) {
+                        sm = res;
+                        r -= 1;
+                    }
+                }
+                return r + 1 - self.size;
+            }
+            sm = M::op(&self.data[r], &sm);
+            if r & r.wrapping_neg() == r {
+                return 0;
+            }
+        }
+    }
+}
+
+#[snippet("RangeAddRangeSum")]
+#[snippet(include = "lazy-segment-tree")]
+/// 区間長つきの加算のモノイド. 要素は(総和, 区間長)で表す
+pub struct SumWithLen<T>(std::marker::PhantomData<T>);
+
+#[snippet("RangeAddRangeSum")]
+impl<T> Monoid for SumWithLen<T>
+where
+    T: num::Num + Copy,
+{
+    type S = (T, T);
+    fn identity() -> (T, T) {
+        (T::zero(), T::zero())
+    }
+    fn op(a: &(T, T), b: &(T, T)) -> (T, T) {
+        (a.0 + b.0, a.1 + b.1)
+    }
+}
+
+#[snippet("RangeAddRangeSum")]
+/// 区間加算
+pub struct RangeAdd<T>(std::marker::PhantomData<T>);
+
+#[snippet("RangeAddRangeSum")]
+impl<T> MapMonoid<SumWithLen<T>> for RangeAdd<T>
+where
+    T: num::Num + Copy,
+{
+    type F = T;
+    fn identity_map() -> T {
+        T::zero()
+    }
+    fn mapping(f: &T, x: &(T, T)) -> (T, T) {
+        (x.0 + *f * x.1, x.1)
+    }
+    fn composition(f: &T, g: &T) -> T {
+        *f + *g
+    }
+}
+
+#[snippet("RangeAddRangeSum")]
+/// 区間加算・区間和取得. 各要素は(値, 1)で初期化すること
+pub type RangeAddRangeSum<T> = LazySegTree<SumWithLen<T>, RangeAdd<T>>;
+
+#[snippet("RangeAssignRangeMin")]
+#[snippet(include = "lazy-segment-tree")]
+#[snippet(include = "Min")]
+#[snippet(include = "Max")]
+/// 区間代入. Noneは恒等写像を表す
+pub struct RangeAssign<T>(std::marker::PhantomData<T>);
+
+#[snippet("RangeAssignRangeMin")]
+impl<T> MapMonoid<Min<T>> for RangeAssign<T>
+where
+    T: num::Bounded + PartialOrd + Copy,
+{
+    type F = Option<T>;
+    fn identity_map() -> Option<T> {
+        None
+    }
+    fn mapping(f: &Option<T>, x: &T) -> T {
+        f.unwrap_or(*x)
+    }
+    fn composition(f: &Option<T>, g: &Option<T>) -> Option<T> {
+        f.or(*g)
+    }
+}
+
+#[snippet("RangeAssignRangeMin")]
+impl<T> MapMonoid<Max<T>> for RangeAssign<T>
+where
+    T: num::Bounded + PartialOrd + Copy,
+{
+    type F = Option<T>;
+    fn identity_map() -> Option<T> {
+        None
+    }
+    fn mapping(f: &Option<T>, x: &T) -> T {
+        f.unwrap_or(*x)
+    }
+    fn composition(f: &Option<T>, g: &Option<T>) -> Option<T> {
+        f.or(*g)
+    }
+}
+
+#[snippet("RangeAssignRangeMin")]
+/// 区間代入・区間最小値取得
+pub type RangeAssignRangeMin<T> = LazySegTree<Min<T>, RangeAssign<T>>;
+
+#[snippet("RangeAssignRangeMin")]
+/// 区間代入・区間最大値取得
+pub type RangeAssignRangeMax<T> = LazySegTree<Max<T>, RangeAssign<T>>;
+
+#[snippet("RangeAffineRangeSumMod")]
+#[snippet(include = "lazy-segment-tree")]
+/// Pを法とする区間長つきの加算のモノイド. 要素は(総和, 区間長)で表す
+pub struct SumWithLenMod<const P: u64>;
+
+#[snippet("RangeAffineRangeSumMod")]
+impl<const P: u64> Monoid for SumWithLenMod<P> {
+    type S = (u64, u64);
+    fn identity() -> (u64, u64) {
+        (0, 0)
+    }
+    fn op(a: &(u64, u64), b: &(u64, u64)) -> (u64, u64) {
+        ((a.0 + b.0) % P, a.1 + b.1)
+    }
+}
+
+#[snippet("RangeAffineRangeSumMod")]
+/// Pを法とする区間アフィン変換 x -> ax + b. 写像は(a, b)で表す
+pub struct RangeAffineMod<const P: u64>;
+
+#[snippet("RangeAffineRangeSumMod")]
+impl<const P: u64> MapMonoid<SumWithLenMod<P>> for RangeAffineMod<P> {
+    type F = (u64, u64);
+    fn identity_map() -> (u64, u64) {
+        (1, 0)
+    }
+    // Pが2^32以上でもオーバーフローしないようu128で計算する
+    fn mapping(f: &(u64, u64), x: &(u64, u64)) -> (u64, u64) {
+        let p = P as u128;
+        let ax = f.0 as u128 * x.0 as u128 % p;
+        let bl = f.1 as u128 * (x.1 % P) as u128 % p;
+        (((ax + bl) % p) as u64, x.1)
+    }
+    fn composition(f: &(u64, u64), g: &(u64, u64)) -> (u64, u64) {
+        let p = P as u128;
+        let a = f.0 as u128 * g.0 as u128 % p;
+        let b = (f.0 as u128 * g.1 as u128 % p + f.1 as u128) % p;
+        (a as u64, b as u64)
+    }
+}
+
+#[snippet("RangeAffineRangeSumMod")]
+/// Pを法とする区間アフィン変換・区間和取得. 各要素は(値, 1)で初期化すること
+pub type RangeAffineRangeSumMod<const P: u64> = LazySegTree<SumWithLenMod<P>, RangeAffineMod<P>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+
+    #[test]
+    fn test_range_add_range_sum() {
+        let n = 10;
+        let mut v: Vec<i64> = (0..n as i64).collect();
+        let mut st = RangeAddRangeSum::from_vec(v.iter().map(|&x| (x, 1)).collect());
+        let mut rng = Rng::default();
+        for _ in 0..200 {
+            let (l, r) = rng.range(n);
+            if rng.next_below(2) == 0 {
+                let x = rng.next_below(21) as i64 - 10;
+                st.apply(l..r, x);
+                for y in v[l..r].iter_mut() {
+                    *y += x;
+                }
+            } else {
+                assert_eq!(st.prod(l..r).0, v[l..r].iter().sum::<i64>());
+            }
+        }
+        assert_eq!(st.all_prod(), (v.iter().sum::<i64>(), n as i64));
+        for (i, &x) in v.iter().enumerate() {
+            assert_eq!(st.get(i), (x, 1));
+        }
+    }
+
+    #[test]
+    fn test_range_assign_range_min() {
+        let n = 13;
+        let mut v: Vec<i64> = vec![i64::MAX; n];
+        let mut st: RangeAssignRangeMin<i64> = LazySegTree::new(n);
+        let mut rng = Rng::new(2463534242);
+        for _ in 0..200 {
+            let (l, r) = rng.range(n);
+            match rng.next_below(3) {
+                0 => {
+                    let x = rng.next_below(100) as i64;
+                    st.apply(l..r, Some(x));
+                    v[l..r].fill(x);
+                }
+                1 => {
+                    let x = rng.next_below(100) as i64;
+                    st.set(l.min(n - 1), x);
+                    v[l.min(n - 1)] = x;
+                }
+                _ => {
+                    let expected = v[l..r].iter().copied().min().unwrap_or(i64::MAX);
+                    assert_eq!(st.prod(l..r), expected);
+                }
+            }
+        }
+
+        let mut st: RangeAssignRangeMax<i32> = LazySegTree::from_vec(vec![1, 5, 3, 2]);
+        st.apply(1..3, Some(0));
+        assert_eq!(st.prod(..), 2);
+        assert_eq!(st.prod(1..3), 0);
+    }
+
+    #[test]
+    fn test_range_affine_range_sum_mod() {
+        const P: u64 = 998244353;
+        let n = 11;
+        let mut v: Vec<u64> = (0..n as u64).map(|i| i * 100_000_007 % P).collect();
+        let mut st: RangeAffineRangeSumMod<P> =
+            LazySegTree::from_vec(v.iter().map(|&x| (x, 1)).collect());
+        let mut rng = Rng::new(314159265358979);
+        for _ in 0..200 {
+            let (l, r) = rng.range(n);
+            if rng.next_below(2) == 0 {
+                let a = rng.next_below(P);
+                let b = rng.next_below(P);
+                st.apply(l..r, (a, b));
+                for y in v[l..r].iter_mut() {
+                    *y = (a * *y + b) % P;
+                }
+            } else {
+                let expected = v[l..r].iter().fold(0, |acc, &x| (acc + x) % P);
+                assert_eq!(st.prod(l..r).0, expected);
+            }
+        }
+
+        // 法が2^32以上
+        const Q: u64 = (1 << 61) - 1;
+        let mut st: RangeAffineRangeSumMod<Q> = LazySegTree::from_vec(vec![(Q - 1, 1); 4]);
+        st.apply(0..3, (Q - 1, Q - 2));
+        st.apply(1..4, (Q - 1, 0));
+        // -x - 2で全て-1のまま、-xで[-1, 1, 1, 1]になる
+        assert_eq!(st.prod(0..4).0, 2);
+        assert_eq!(st.prod(0..1).0, Q - 1);
+    }
+
+    #[test]
+    fn test_lazy_segment_tree_max_right_min_left() {
+        let n = 9;
+        let mut v: Vec<i64> = vec![1; n];
+        let mut st = RangeAddRangeSum::from_vec(v.iter().map(|&x| (x, 1)).collect());
+        st.apply(2..6, 2);
+        for y in v[2..6].iter_mut() {
+            *y += 2;
+        }
+        // 全探索と一致すること
+        for x in 0..30 {
+            for l in 0..=n {
+                let expected = (l..=n)
+                    .filter(|&r| v[l..r].iter().sum::<i64>() <= x)
+                    .max()
+                    .unwrap();
+                assert_eq!(st.max_right(l, |s| s.0 <= x), expected);
+            }
+            for r in 0..=n {
+                let expected = (0..=r)
+                    .filter(|&l| v[l..r].iter().sum::<i64>() <= x)
+                    .min()
+                    .unwrap();
+                assert_eq!(st.min_left(r, |s| s.0 <= x), expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,571 @@
+use cargo_snippet::snippet;
+
+#[snippet("eval_linear")]
+/// 直線 y = ax + b のxにおける値. オーバーフローを避けるためi128で計算する
+pub fn eval_linear(line: (i64, i64), x: i64) -> i128 {
+    line.0 as i128 * x as i128 + line.1 as i128
+}
+
+#[snippet("eval_linear")]
+// 最大値を求める場合（sign = -1）に符号を反転した直線. 係数がi64::MINの場合は反転できない
+fn signed_line(a: i64, b: i64, sign: i64) -> (i64, i64) {
+    assert!(
+        sign == 1 || (a != i64::MIN && b != i64::MIN),
+        "coefficient out of range"
+    );
+    (sign * a, sign * b)
+}
+
+#[snippet("li-chao-tree")]
+#[snippet(include = "eval_linear")]
+/// Li Chao Tree. 直線（線分）の追加と、あらかじめ与えたx座標での最小値（最大値）の取得をO(log n)で行う
+pub struct LiChaoTree {
+    // クエリに使うx座標（ソート済み）
+    xs: Vec<i64>,
+    // 葉の数（xsの要素数以上の最小の2冪）
+    size: usize,
+    // 各ノードで最良の直線. 最大値を求める場合は符号を反転して持つ
+    lines: Vec<Option<(i64, i64)>>,
+    // 最小値なら1, 最大値なら-1
+    sign: i64,
+}
+
+#[snippet("li-chao-tree")]
+impl LiChaoTree {
+    /// 最小値を求めるLi Chao Treeを作る. xsはクエリに使うx座標
+    pub fn new(xs: &[i64]) -> Self {
+        Self::with_sign(xs, 1)
+    }
+
+    /// 最大値を求めるLi Chao Treeを作る. xsはクエリに使うx座標. 追加する直線の係数はi64::MINより大きいこと
+    pub fn new_max(xs: &[i64]) -> Self {
+        Self::with_sign(xs, -1)
+    }
+
+    fn with_sign(xs: &[i64], sign: i64) -> Self {
+        let mut xs = xs.to_vec();
+        xs.sort();
+        xs.dedup();
+        let size = xs.len().next_power_of_two();
+        Self {
+            xs,
+            size,
+            lines: vec![None; 2 * size],
+            sign,
+        }
+    }
+
+    // i番目のx座標. 範囲外は末尾の座標とする
+    fn x(&self, i: usize) -> i64 {
+        self.xs[i.min(self.xs.len() - 1)]
+    }
+
+    // ノードk（区間[l, r]）に直線を追加する
+    fn add_rec(&mut self, mut line: (i64, i64), k: usize, l: usize, r: usize) {
+        let Some(mut cur) = self.lines[k] else {
+            self.lines[k] = Some(line);
+            return;
+        };
+        let m = (l + r) / 2;
+        let (xl, xm, xr) = (self.x(l), self.x(m), self.x(r));
+        let lb = eval_linear(line, xl) < eval_linear(cur, xl);
+        let mb = eval_linear(line, xm) < eval_linear(cur, xm);
+        let rb = eval_linear(line, xr) < eval_linear(cur, xr);
+        // 中央で良い方をノードに残し、もう一方を交点のある側の子に渡す
+        if mb {
+            std::mem::swap(&mut line, &mut cur);
+            self.lines[k] = Some(cur);
+        }
+        if l == r {
+            return;
+        }
+        if lb != mb {
+            self.add_rec(line, 2 * k, l, m);
+        } else if rb != mb {
+            self.add_rec(line, 2 * k + 1, m + 1, r);
+        }
+    }
+
+    /// 直線 y = ax + b を追加する
+    pub fn add_line(&mut self, a: i64, b: i64) {
+        if self.xs.is_empty() {
+            return;
+        }
+        let line = signed_line(a, b, self.sign);
+        self.add_rec(line, 1, 0, self.size - 1);
+    }
+
+    /// x座標が[xl, xr)の範囲にある線分 y = ax + b を追加する
+    pub fn add_segment(&mut self, a: i64, b: i64, xl: i64, xr: i64) {
+        let line = signed_line(a, b, self.sign);
+        let l = self.xs.partition_point(|&x| x < xl);
+        let r = self.xs.partition_point(|&x| x < xr);
+        self.add_segment_rec(line, l, r, 1, 0, self.size);
+    }
+
+    // ノードk（区間[nl, nr)）のうち[l, r)に含まれる部分に直線を追加する
+    fn add_segment_rec(
+        &mut self,
+        line: (i64, i64),
+        l: usize,
+        r: usize,
+        k: usize,
+        nl: usize,
+        nr: usize,
+    ) {
+        if r <= nl || nr <= l {
+            return;
+        }
+        if l <= nl && nr <= r {
+            self.add_rec(line, k, nl, nr - 1);
+            return;
+        }
+        let m = (nl + nr) / 2;
+        self.add_segment_rec(line, l, r, 2 * k, nl, m);
+        self.add_segment_rec(line, l, r, 2 * k + 1, m, nr);
+    }
+
+    /// x座標xにおける最小値（最大値）を求める. xは事前に与えたx座標であること
+    pub fn query(&self, x: i64) -> Option<i128> {
+        let i = self.xs.partition_point(|&v| v < x);
+        assert!(i < self.xs.len() && self.xs[i] == x, "unknown x");
+        // 葉から根までの直線を調べる
+        let mut k = i + self.size;
+        let mut res: Option<i128> = None;
+        while k > 0 {
+            if let Some(line) = self.lines[k] {
+                let y = eval_linear(line, x);
+                res = Some(res.map_or(y, |r| r.min(y)));
+            }
+            k >>= 1;
+        }
+        res.map(|r| r * self.sign as i128)
+    }
+}
+
+#[snippet("dynamic-li-chao-tree")]
+// (l + r) / 2 の切り捨て. l, rがi64の端点でもオーバーフローしない
+fn mid(l: i64, r: i64) -> i64 {
+    (l & r) + ((l ^ r) >> 1)
+}
+
+#[snippet("dynamic-li-chao-tree")]
+#[snippet(include = "eval_linear")]
+struct LiChaoNode {
+    line: Option<(i64, i64)>,
+    // 子ノードの番号. 存在しない場合はusize::MAX
+    left: usize,
+    right: usize,
+}
+
+#[snippet("dynamic-li-chao-tree")]
+/// 動的にノードを作成するLi Chao Tree. 整数区間[lo, hi]上での最小値（最大値）の取得をO(log (hi - lo))で行う
+pub struct DynamicLiChaoTree {
+    lo: i64,
+    hi: i64,
+    nodes: Vec<LiChaoNode>,
+    // 最小値なら1, 最大値なら-1
+    sign: i64,
+}
+
+#[snippet("dynamic-li-chao-tree")]
+impl DynamicLiChaoTree {
+    /// 区間[lo, hi]で最小値を求めるLi Chao Treeを作る
+    pub fn new(lo: i64, hi: i64) -> Self {
+        Self::with_sign(lo, hi, 1)
+    }
+
+    /// 区間[lo, hi]で最大値を求めるLi Chao Treeを作る. 追加する直線の係数はi64::MINより大きいこと
+    pub fn new_max(lo: i64, hi: i64) -> Self {
+        Self::with_sign(lo, hi, -1)
+    }
+
+    fn with_sign(lo: i64, hi: i64, sign: i64) -> Self {
+        assert!(lo <= hi);
+        let mut t = Self {
+            lo,
+            hi,
+            nodes: vec![],
+            sign,
+        };
+        t.new_node();
+        t
+    }
+
+    fn new_node(&mut self) -> usize {
+        self.nodes.push(LiChaoNode {
+            line: None,
+            left: usize::MAX,
+            right: usize::MAX,
+        });
+        self.nodes.len() - 1
+    }
+
+    fn child(&mut self, k: usize, is_left: bool) -> usize {
+        let c = if is_left {
+            self.nodes[k].left
+        } else {
+            self.nodes[k].right
+        };
+        if c != usize::MAX {
+            return c;
+        }
+        let c = self.new_node();
+        if is_left {
+            self.nodes[k].left = c;
+        } else {
+            self.nodes[k].right = c;
+        }
+        c
+    }
+
+    // ノードk（区間[l, r]）に直線を追加する
+    fn add_rec(&mut self, mut line: (i64, i64), k: usize, l: i64, r: i64) {
+        let Some(mut cur) = self.nodes[k].line else {
+            self.nodes[k].line = Some(line);
+            return;
+        };
+        let m = mid(l, r);
+        let lb = eval_linear(line, l) < eval_linear(cur, l);
+        let mb = eval_linear(line, m) < eval_linear(cur, m);
+        let rb = eval_linear(line, r) < eval_linear(cur, r);
+        // 中央で良い方をノードに残し、もう一方を交点のある側の子に渡す
+        if mb {
+            std::mem::swap(&mut line, &mut cur);
+            self.nodes[k].line = Some(cur);
+        }
+        if l == r {
+            return;
+        }
+        if lb != mb {
+            let c = self.child(k, true);
+            self.add_rec(line, c, l, m);
+        } else if rb != mb {
+            let c = self.child(k, false);
+            self.add_rec(line, c, m + 1, r);
+        }
+    }
+
+    /// 直線 y = ax + b を追加する
+    pub fn add_line(&mut self, a: i64, b: i64) {
+        let line = signed_line(a, b, self.sign);
+        self.add_rec(line, 0, self.lo, self.hi);
+    }
+
+    /// x座標が[xl, xr)の範囲にある線分 y = ax + b を追加する
+    pub fn add_segment(&mut self, a: i64, b: i64, xl: i64, xr: i64) {
+        let line = signed_line(a, b, self.sign);
+        if xl >= xr {
+            return;
+        }
+        // 閉区間[ql, qr]にする. xl < xrなのでxr - 1はオーバーフローしない
+        let ql = xl.max(self.lo);
+        let qr = (xr - 1).min(self.hi);
+        if ql > qr {
+            return;
+        }
+        self.add_segment_rec(line, ql, qr, 0, self.lo, self.hi);
+    }
+
+    // ノードk（区間[l, r]）のうち[ql, qr]に含まれる部分に直線を追加する
+    fn add_segment_rec(&mut self, line: (i64, i64), ql: i64, qr: i64, k: usize, l: i64, r: i64) {
+        if qr < l || r < ql {
+            return;
+        }
+        if ql <= l && r <= qr {
+            self.add_rec(line, k, l, r);
+            return;
+        }
+        let m = mid(l, r);
+        let c = self.child(k, true);
+        self.add_segment_rec(line, ql, qr, c, l, m);
+        let c = self.child(k, false);
+        self.add_segment_rec(line, ql, qr, c, m + 1, r);
+    }
+
+    /// x座標xにおける最小値（最大値）を求める
+    pub fn query(&self, x: i64) -> Option<i128> {
+        assert!(self.lo <= x && x <= self.hi);
+        let (mut k, mut l, mut r) = (0, self.lo, self.hi);
+        let mut res: Option<i128> = None;
+        while k != usize::MAX {
+            if let Some(line) = self.nodes[k].line {
+                let y = eval_linear(line, x);
+                res = Some(res.map_or(y, |v| v.min(y)));
+            }
+            let m = mid(l, r);
+            if x <= m {
+                k = self.nodes[k].left;
+                r = m;
+            } else {
+                k = self.nodes[k].right;
+                l = m + 1;
+            }
+        }
+        res.map(|v| v * self.sign as i128)
+    }
+}
+
+#[snippet("monotone-cht")]
+#[snippet(include = "eval_linear")]
+/// 傾きが単調な直線の追加と最小値（最大値）の取得を行うConvex Hull Trick
+/// 最小値の場合は傾きが単調非増加、最大値の場合は単調非減少の順に直線を追加すること
+pub struct MonotoneCht {
+    // 下側凸包をなす直線. 最大値を求める場合は符号を反転して持つ
+    lines: std::collections::VecDeque<(i64, i64)>,
+    // 最小値なら1, 最大値なら-1
+    sign: i64,
+}
+
+#[snippet("monotone-cht")]
+impl MonotoneCht {
+    /// 最小値を求めるCHTを作る
+    pub fn new() -> Self {
+        Self {
+            lines: std::collections::VecDeque::new(),
+            sign: 1,
+        }
+    }
+
+    /// 最大値を求めるCHTを作る. 追加する直線の係数はi64::MINより大きいこと
+    pub fn new_max() -> Self {
+        Self {
+            lines: std::collections::VecDeque::new(),
+            sign: -1,
+        }
+    }
+
+    // l2がl1, l3に対して不要か（傾きはl1 > l2 > l3）
+    fn is_unnecessary(l1: (i64, i64), l2: (i64, i64), l3: (i64, i64)) -> bool {
+        let (a1, b1) = (l1.0 as i128, l1.1 as i128);
+        let (a2, b2) = (l2.0 as i128, l2.1 as i128);
+        let (a3, b3) = (l3.0 as i128, l3.1 as i128);
+        (b3 - b1) * (a1 - a2) <= (b2 - b1) * (a1 - a3)
+    }
+
+    /// 直線 y = ax + b を追加する
+    pub fn add_line(&mut self, a: i64, b: i64) {
+        let line = signed_line(a, b, self.sign);
+        if let Some(&last) = self.lines.back() {
+            assert!(line.0 <= last.0, "slopes must be monotone");
+            if line.0 == last.0 {
+                if line.1 >= last.1 {
+                    return;
+                }
+                self.lines.pop_back();
+            }
+        }
+        while self.lines.len() >= 2 {
+            let l = self.lines.len();
+            if !Self::is_unnecessary(self.lines[l - 2], self.lines[l - 1], line) {
+                break;
+            }
+            self.lines.pop_back();
+        }
+        self.lines.push_back(line);
+    }
+
+    /// x座標xにおける最小値（最大値）を求める（二分探索）
+    pub fn query(&self, x: i64) -> Option<i128> {
+        if self.lines.is_empty() {
+            return None;
+        }
+        // x座標が大きいほど後ろの直線が最良になる
+        let (mut lo, mut hi) = (0, self.lines.len() - 1);
+        while lo < hi {
+            let m = (lo + hi) / 2;
+            if eval_linear(self.lines[m], x) > eval_linear(self.lines[m + 1], x) {
+                lo = m + 1;
+            } else {
+                hi = m;
+            }
+        }
+        Some(eval_linear(self.lines[lo], x) * self.sign as i128)
+    }
+
+    /// x座標xにおける最小値（最大値）を求める. xは呼び出しごとに単調非減少であること（償却O(1)）
+    pub fn query_monotone(&mut self, x: i64) -> Option<i128> {
+        while self.lines.len() >= 2
+            && eval_linear(self.lines[0], x) >= eval_linear(self.lines[1], x)
+        {
+            self.lines.pop_front();
+        }
+        self.lines
+            .front()
+            .map(|&line| eval_linear(line, x) * self.sign as i128)
+    }
+}
+
+#[snippet("monotone-cht")]
+impl Default for MonotoneCht {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 全探索で最小値を求める
+    fn brute(lines: &[(i64, i64, i64, i64)], x: i64) -> Option<i128> {
+        lines
+            .iter()
+            .filter(|l| l.2 <= x && x < l.3)
+            .map(|l| eval_linear((l.0, l.1), x))
+            .min()
+    }
+
+    #[test]
+    fn test_li_chao_tree() {
+        let xs: Vec<i64> = (-10..=10).collect();
+        let mut lct = LiChaoTree::new(&xs);
+        assert_eq!(lct.query(0), None);
+        let lines = [(2, 3), (-1, 5), (0, -2), (3, -10), (-4, -20), (1, 1)];
+        let mut added = vec![];
+        for &(a, b) in lines.iter() {
+            lct.add_line(a, b);
+            added.push((a, b, i64::MIN, i64::MAX));
+            for &x in xs.iter() {
+                assert_eq!(lct.query(x), brute(&added, x));
+            }
+        }
+
+        // 最大値
+        let mut lct = LiChaoTree::new_max(&[-5, 0, 3, 100]);
+        lct.add_line(1, 0);
+        lct.add_line(-1, 0);
+        assert_eq!(lct.query(-5), Some(5));
+        assert_eq!(lct.query(3), Some(3));
+        assert_eq!(lct.query(100), Some(100));
+    }
+
+    #[test]
+    fn test_li_chao_tree_segment() {
+        let xs: Vec<i64> = (0..20).map(|i| i * i - 50).collect();
+        let mut lct = LiChaoTree::new(&xs);
+        let segments = [
+            (1, 0, -50, 100),
+            (-2, 10, 0, 200),
+            (0, -30, -100, -10),
+            (3, -100, 50, 51),
+            (-1, 0, 150, 400),
+        ];
+        let mut added = vec![];
+        for &(a, b, l, r) in segments.iter() {
+            lct.add_segment(a, b, l, r);
+            added.push((a, b, l, r));
+            for &x in xs.iter() {
+                assert_eq!(lct.query(x), brute(&added, x));
+            }
+        }
+    }
+
+    #[test]
+    fn test_dynamic_li_chao_tree() {
+        let mut lct = DynamicLiChaoTree::new(-1_000_000_000, 1_000_000_000);
+        assert_eq!(lct.query(0), None);
+        let xs: Vec<i64> = vec![-1_000_000_000, -12345, -1, 0, 7, 999, 1_000_000_000];
+        let segments = [
+            (1_000_000_000, 0, i64::MIN, i64::MAX),
+            (-3, 7, -1000, 1000),
+            (0, -5, 0, 8),
+            (
+                -1_000_000_000_000_000_000,
+                -1_000_000_000,
+                -1,
+                1_000_000_001,
+            ),
+        ];
+        let mut added = vec![];
+        for &(a, b, l, r) in segments.iter() {
+            if l == i64::MIN {
+                lct.add_line(a, b);
+            } else {
+                lct.add_segment(a, b, l, r);
+            }
+            added.push((a, b, l, r));
+            for &x in xs.iter() {
+                assert_eq!(lct.query(x), brute(&added, x));
+            }
+        }
+        // i64に収まらない値
+        assert_eq!(
+            lct.query(1_000_000_000),
+            Some(-1_000_000_000_000_000_000_000_000_000 - 1_000_000_000)
+        );
+        assert!(lct.query(1_000_000_000).unwrap() < i64::MIN as i128);
+
+        // x座標の範囲がi64全体
+        let mut lct = DynamicLiChaoTree::new(i64::MIN, i64::MAX);
+        lct.add_segment(1, 0, 0, i64::MAX);
+        lct.add_segment(-1, 0, i64::MIN, i64::MAX);
+        lct.add_segment(0, -1, i64::MAX, i64::MAX);
+        assert_eq!(lct.query(i64::MIN), Some(-(i64::MIN as i128)));
+        assert_eq!(lct.query(0), Some(0));
+        assert_eq!(lct.query(i64::MAX - 1), Some(-(i64::MAX as i128) + 1));
+        assert_eq!(lct.query(i64::MAX), None);
+        lct.add_line(0, 5);
+        assert_eq!(lct.query(i64::MAX), Some(5));
+
+        // 最大値
+        let mut lct = DynamicLiChaoTree::new_max(0, 10);
+        lct.add_line(-1, 5);
+        lct.add_segment(2, 0, 3, 6);
+        assert_eq!(lct.query(0), Some(5));
+        assert_eq!(lct.query(5), Some(10));
+        assert_eq!(lct.query(6), Some(-1));
+    }
+
+    #[test]
+    fn test_monotone_cht() {
+        // 傾きが単調非増加
+        let lines = [
+            (5, -10),
+            (3, 0),
+            (3, -2),
+            (1, 5),
+            (0, 3),
+            (-2, 20),
+            (-4, 30),
+        ];
+        let mut cht = MonotoneCht::new();
+        assert_eq!(cht.query(0), None);
+        let mut added = vec![];
+        for &(a, b) in lines.iter() {
+            cht.add_line(a, b);
+            added.push((a, b, i64::MIN, i64::MAX));
+            for x in -20..=20 {
+                assert_eq!(cht.query(x), brute(&added, x));
+            }
+        }
+        for x in -20..=20 {
+            assert_eq!(cht.query_monotone(x), brute(&added, x));
+        }
+
+        // 最大値（傾きが単調非減少）
+        let mut cht = MonotoneCht::new_max();
+        cht.add_line(-1, 0);
+        cht.add_line(0, -3);
+        cht.add_line(1, 0);
+        assert_eq!(cht.query(-5), Some(5));
+        assert_eq!(cht.query(0), Some(0));
+        assert_eq!(cht.query(4), Some(4));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_monotone_cht_panic() {
+        let mut cht = MonotoneCht::new();
+        cht.add_line(1, 0);
+        cht.add_line(2, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_dynamic_li_chao_tree_max_coefficient() {
+        // 最大値の場合、符号を反転できない係数は受け付けない
+        let mut lct = DynamicLiChaoTree::new_max(0, 10);
+        lct.add_line(i64::MIN, 0);
+    }
+}
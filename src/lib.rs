@@ -2,7 +2,23 @@ pub mod binary_search;
 pub mod collection;
 pub mod cumulative_sum;
 pub mod enumerate;
+pub mod fenwick_tree;
+pub mod geometry;
+pub mod heap;
+pub mod lazy_segment_tree;
+pub mod li_chao_tree;
 pub mod math;
 pub mod max_flow;
+pub mod mo;
+pub mod monotone_stack;
+pub mod persistent_segment_tree;
+pub mod segment_tree;
+pub mod sparse_table;
+pub mod sqrt_decomposition;
 pub mod string;
+pub mod treap;
 pub mod union_find;
+pub mod wavelet_matrix;
+
+#[cfg(test)]
+mod test_util;
@@ -69,7 +69,7 @@ where
 
     let zero = T::zero();
     let mut num = if n > zero { n } else { -n };
-    let mut sum = zero.clone();
+    let mut sum = zero;
 
     while num > zero {
         sum = sum + (num % base);
@@ -34,7 +34,7 @@ pub struct MaxFlow {
 impl MaxFlow {
     pub fn new(size: usize) -> Self {
         Self {
-            size: size,
+            size,
             graph: vec![vec![]; size + 1],
         }
     }
@@ -92,9 +92,7 @@ impl MaxFlow {
             }
             total_flow += flow;
             // 初期化
-            for i in 0..is_visited.len() {
-                is_visited[i] = false;
-            }
+            is_visited.fill(false);
         }
         total_flow
     }
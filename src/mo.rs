@@ -0,0 +1,400 @@
+use crate::cumulative_sum::range_to_pair;
+use cargo_snippet::snippet;
+
+#[snippet("mo")]
+#[snippet(include = "range_to_pair")]
+/// Moのアルゴリズムで管理する区間の状態
+pub trait MoState {
+    type Output;
+    /// 区間の左端にi番目の要素を追加する
+    fn add_left(&mut self, i: usize);
+    /// 区間の右端にi番目の要素を追加する
+    fn add_right(&mut self, i: usize);
+    /// 区間の左端からi番目の要素を削除する
+    fn remove_left(&mut self, i: usize);
+    /// 区間の右端からi番目の要素を削除する
+    fn remove_right(&mut self, i: usize);
+    /// 現在の区間に対する答え
+    fn answer(&self) -> Self::Output;
+}
+
+#[snippet("mo")]
+/// Moのアルゴリズム. 長さnの列に対するq個の区間クエリをO((n + q)√n)回の追加・削除で処理する. インデックスは0-origin
+pub struct Mo {
+    n: usize,
+    queries: Vec<(usize, usize)>,
+}
+
+#[snippet("mo")]
+impl Mo {
+    pub fn new(n: usize) -> Self {
+        Self { n, queries: vec![] }
+    }
+
+    /// クエリを追加する. 答えは追加した順に返す
+    pub fn add_query<R>(&mut self, range: R)
+    where
+        R: std::ops::RangeBounds<usize>,
+    {
+        self.queries.push(range_to_pair(range, self.n));
+    }
+
+    /// ブロックごとに左端で分け、右端を往復させる順にクエリを処理する
+    pub fn run<S>(&self, state: &mut S) -> Vec<S::Output>
+    where
+        S: MoState,
+    {
+        let q = self.queries.len().max(1);
+        let width = (self.n / (q as f64).sqrt().ceil() as usize).max(1);
+        let mut order: Vec<usize> = (0..self.queries.len()).collect();
+        order.sort_by_key(|&i| {
+            let (l, r) = self.queries[i];
+            let b = l / width;
+            // 奇数番目のブロックでは右端を降順にする
+            (b, if b & 1 == 0 { r } else { self.n - r })
+        });
+        self.process(&order, state)
+    }
+
+    /// ヒルベルト曲線の順にクエリを処理する
+    pub fn run_hilbert<S>(&self, state: &mut S) -> Vec<S::Output>
+    where
+        S: MoState,
+    {
+        let mut log = 0;
+        while (1 << log) <= self.n {
+            log += 1;
+        }
+        let mut order: Vec<usize> = (0..self.queries.len()).collect();
+        order.sort_by_cached_key(|&i| {
+            let (l, r) = self.queries[i];
+            Self::hilbert_order(l, r, log)
+        });
+        self.process(&order, state)
+    }
+
+    // 2^log × 2^logの格子上の点(x, y)のヒルベルト曲線上の位置
+    fn hilbert_order(x: usize, y: usize, log: usize) -> u64 {
+        let (mut x, mut y) = (x as u64, y as u64);
+        let mut d = 0;
+        let mut s = 1u64 << log >> 1;
+        while s > 0 {
+            let rx = (x & s > 0) as u64;
+            let ry = (y & s > 0) as u64;
+            d += s * s * ((3 * rx) ^ ry);
+            if ry == 0 {
+                if rx == 1 {
+                    x = s - 1 - (x & (s - 1));
+                    y = s - 1 - (y & (s - 1));
+                }
+                std::mem::swap(&mut x, &mut y);
+            }
+            s >>= 1;
+        }
+        d
+    }
+
+    fn process<S>(&self, order: &[usize], state: &mut S) -> Vec<S::Output>
+    where
+        S: MoState,
+    {
+        let mut res: Vec<Option<S::Output>> = (0..self.queries.len()).map(|_| None).collect();
+        let (mut cl, mut cr) = (0, 0);
+        for &i in order.iter() {
+            let (l, r) = self.queries[i];
+            // 区間を広げてから縮める
+            while cl > l {
+                cl -= 1;
+                state.add_left(cl);
+            }
+            while cr < r {
+                state.add_right(cr);
+                cr += 1;
+            }
+            while cl < l {
+                state.remove_left(cl);
+                cl += 1;
+            }
+            while cr > r {
+                cr -= 1;
+                state.remove_right(cr);
+            }
+            res[i] = Some(state.answer());
+        }
+        res.into_iter().map(|x| x.unwrap()).collect()
+    }
+}
+
+#[snippet("rollback-mo")]
+#[snippet(include = "range_to_pair")]
+/// 削除ができない代わりに、ある時点の状態に戻せる区間の状態
+pub trait RollbackMoState {
+    type Output;
+    /// 区間の左端にi番目の要素を追加する
+    fn add_left(&mut self, i: usize);
+    /// 区間の右端にi番目の要素を追加する
+    fn add_right(&mut self, i: usize);
+    /// 現在の状態を記録する
+    fn snapshot(&mut self);
+    /// 最後に記録した状態に戻す
+    fn rollback(&mut self);
+    /// 空の区間の状態に戻す
+    fn reset(&mut self);
+    /// 現在の区間に対する答え
+    fn answer(&self) -> Self::Output;
+}
+
+#[snippet("rollback-mo")]
+/// 追加とロールバックのみで区間クエリを処理するMoのアルゴリズム. インデックスは0-origin
+pub struct RollbackMo {
+    n: usize,
+    queries: Vec<(usize, usize)>,
+}
+
+#[snippet("rollback-mo")]
+impl RollbackMo {
+    pub fn new(n: usize) -> Self {
+        Self { n, queries: vec![] }
+    }
+
+    /// クエリを追加する. 答えは追加した順に返す
+    pub fn add_query<R>(&mut self, range: R)
+    where
+        R: std::ops::RangeBounds<usize>,
+    {
+        self.queries.push(range_to_pair(range, self.n));
+    }
+
+    pub fn run<S>(&self, state: &mut S) -> Vec<S::Output>
+    where
+        S: RollbackMoState,
+    {
+        let q = self.queries.len().max(1);
+        let width = (self.n / (q as f64).sqrt().ceil() as usize).max(1);
+        let mut order: Vec<usize> = (0..self.queries.len()).collect();
+        order.sort_by_key(|&i| {
+            let (l, r) = self.queries[i];
+            (l / width, r)
+        });
+        let mut res: Vec<Option<S::Output>> = (0..self.queries.len()).map(|_| None).collect();
+        let mut last_block = usize::MAX;
+        // 右側の区間は[border, cr)
+        let (mut border, mut cr) = (0, 0);
+        for &i in order.iter() {
+            let (l, r) = self.queries[i];
+            let b = l / width;
+            if b != last_block {
+                state.reset();
+                last_block = b;
+                border = ((b + 1) * width).min(self.n);
+                cr = border;
+            }
+            if r <= border {
+                // ブロック内に収まるクエリは直接計算する
+                state.snapshot();
+                for j in l..r {
+                    state.add_right(j);
+                }
+                res[i] = Some(state.answer());
+                state.rollback();
+                continue;
+            }
+            while cr < r {
+                state.add_right(cr);
+                cr += 1;
+            }
+            state.snapshot();
+            for j in (l..border).rev() {
+                state.add_left(j);
+            }
+            res[i] = Some(state.answer());
+            state.rollback();
+        }
+        res.into_iter().map(|x| x.unwrap()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+
+    // 区間内の値の種類数
+    struct Distinct<'a> {
+        v: &'a [usize],
+        cnt: Vec<usize>,
+        kinds: usize,
+    }
+
+    impl Distinct<'_> {
+        fn add(&mut self, i: usize) {
+            self.cnt[self.v[i]] += 1;
+            if self.cnt[self.v[i]] == 1 {
+                self.kinds += 1;
+            }
+        }
+
+        fn remove(&mut self, i: usize) {
+            self.cnt[self.v[i]] -= 1;
+            if self.cnt[self.v[i]] == 0 {
+                self.kinds -= 1;
+            }
+        }
+    }
+
+    impl MoState for Distinct<'_> {
+        type Output = usize;
+        fn add_left(&mut self, i: usize) {
+            self.add(i);
+        }
+        fn add_right(&mut self, i: usize) {
+            self.add(i);
+        }
+        fn remove_left(&mut self, i: usize) {
+            self.remove(i);
+        }
+        fn remove_right(&mut self, i: usize) {
+            self.remove(i);
+        }
+        fn answer(&self) -> usize {
+            self.kinds
+        }
+    }
+
+    // 区間内で同じ値をとる2つの位置の距離の最大値. 削除ができない
+    struct MaxDistance<'a> {
+        v: &'a [usize],
+        first: Vec<usize>,
+        last: Vec<usize>,
+        max: usize,
+        // 変更履歴（値, first, last）と記録時点の履歴の長さ・最大値
+        history: Vec<(usize, usize, usize)>,
+        saved: (usize, usize),
+    }
+
+    impl MaxDistance<'_> {
+        fn update(&mut self, x: usize, first: usize, last: usize) {
+            self.history.push((x, self.first[x], self.last[x]));
+            self.first[x] = first;
+            self.last[x] = last;
+            if first != usize::MAX {
+                self.max = self.max.max(last - first);
+            }
+        }
+    }
+
+    impl RollbackMoState for MaxDistance<'_> {
+        type Output = usize;
+        fn add_left(&mut self, i: usize) {
+            let x = self.v[i];
+            let last = if self.first[x] == usize::MAX {
+                i
+            } else {
+                self.last[x]
+            };
+            self.update(x, i, last);
+        }
+        fn add_right(&mut self, i: usize) {
+            let x = self.v[i];
+            let first = if self.first[x] == usize::MAX {
+                i
+            } else {
+                self.first[x]
+            };
+            self.update(x, first, i);
+        }
+        fn snapshot(&mut self) {
+            self.saved = (self.history.len(), self.max);
+        }
+        fn rollback(&mut self) {
+            while self.history.len() > self.saved.0 {
+                let (x, first, last) = self.history.pop().unwrap();
+                self.first[x] = first;
+                self.last[x] = last;
+            }
+            self.max = self.saved.1;
+        }
+        fn reset(&mut self) {
+            self.first.fill(usize::MAX);
+            self.last.fill(usize::MAX);
+            self.max = 0;
+            self.history.clear();
+        }
+        fn answer(&self) -> usize {
+            self.max
+        }
+    }
+
+    fn random_input(n: usize, m: usize) -> (Vec<usize>, Vec<(usize, usize)>) {
+        let mut rng = Rng::default();
+        let v: Vec<usize> = (0..n).map(|_| rng.next_usize(m)).collect();
+        let queries = (0..200).map(|_| rng.range(n)).collect();
+        (v, queries)
+    }
+
+    #[test]
+    fn test_mo() {
+        let (v, queries) = random_input(100, 10);
+        let mut mo = Mo::new(v.len());
+        for &(l, r) in queries.iter() {
+            mo.add_query(l..r);
+        }
+        let expected: Vec<usize> = queries
+            .iter()
+            .map(|&(l, r)| {
+                let mut s = v[l..r].to_vec();
+                s.sort();
+                s.dedup();
+                s.len()
+            })
+            .collect();
+        let mut state = Distinct {
+            v: &v,
+            cnt: vec![0; 10],
+            kinds: 0,
+        };
+        assert_eq!(mo.run(&mut state), expected);
+        let mut state = Distinct {
+            v: &v,
+            cnt: vec![0; 10],
+            kinds: 0,
+        };
+        assert_eq!(mo.run_hilbert(&mut state), expected);
+
+        // クエリなし
+        let mo = Mo::new(5);
+        assert!(mo.run(&mut state).is_empty());
+    }
+
+    #[test]
+    fn test_rollback_mo() {
+        let (v, queries) = random_input(100, 10);
+        let mut mo = RollbackMo::new(v.len());
+        for &(l, r) in queries.iter() {
+            mo.add_query(l..r);
+        }
+        let expected: Vec<usize> = queries
+            .iter()
+            .map(|&(l, r)| {
+                let mut res = 0;
+                for i in l..r {
+                    for j in i..r {
+                        if v[i] == v[j] {
+                            res = res.max(j - i);
+                        }
+                    }
+                }
+                res
+            })
+            .collect();
+        let mut state = MaxDistance {
+            v: &v,
+            first: vec![usize::MAX; 10],
+            last: vec![usize::MAX; 10],
+            max: 0,
+            history: vec![],
+            saved: (0, 0),
+        };
+        assert_eq!(mo.run(&mut state), expected);
+    }
+}
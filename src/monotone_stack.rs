@@ -0,0 +1,294 @@
+use cargo_snippet::snippet;
+
+#[snippet("monotone_stack")]
+// 各iについて、is_target(x[j], x[i])を満たす最大のj < iを求める
+// is_targetは<, <=, >, >=のいずれかであること
+fn prev_index_by<T, F>(x: &[T], is_target: F) -> Vec<Option<usize>>
+where
+    F: Fn(&T, &T) -> bool,
+{
+    let mut res = vec![None; x.len()];
+    let mut stack: Vec<usize> = vec![];
+    for i in 0..x.len() {
+        while let Some(&j) = stack.last() {
+            if is_target(&x[j], &x[i]) {
+                break;
+            }
+            stack.pop();
+        }
+        res[i] = stack.last().copied();
+        stack.push(i);
+    }
+    res
+}
+
+#[snippet("monotone_stack")]
+// 各iについて、is_target(x[j], x[i])を満たす最小のj > iを求める
+fn next_index_by<T, F>(x: &[T], is_target: F) -> Vec<Option<usize>>
+where
+    F: Fn(&T, &T) -> bool,
+{
+    let mut res = vec![None; x.len()];
+    let mut stack: Vec<usize> = vec![];
+    for i in (0..x.len()).rev() {
+        while let Some(&j) = stack.last() {
+            if is_target(&x[j], &x[i]) {
+                break;
+            }
+            stack.pop();
+        }
+        res[i] = stack.last().copied();
+        stack.push(i);
+    }
+    res
+}
+
+#[snippet("monotone_stack")]
+/// 各iについて、x[j] < x[i]を満たす最大のj < i
+pub fn prev_smaller<T>(x: &[T]) -> Vec<Option<usize>>
+where
+    T: PartialOrd,
+{
+    prev_index_by(x, |a, b| a < b)
+}
+
+#[snippet("monotone_stack")]
+/// 各iについて、x[j] <= x[i]を満たす最大のj < i
+pub fn prev_smaller_or_equal<T>(x: &[T]) -> Vec<Option<usize>>
+where
+    T: PartialOrd,
+{
+    prev_index_by(x, |a, b| a <= b)
+}
+
+#[snippet("monotone_stack")]
+/// 各iについて、x[j] > x[i]を満たす最大のj < i
+pub fn prev_greater<T>(x: &[T]) -> Vec<Option<usize>>
+where
+    T: PartialOrd,
+{
+    prev_index_by(x, |a, b| a > b)
+}
+
+#[snippet("monotone_stack")]
+/// 各iについて、x[j] >= x[i]を満たす最大のj < i
+pub fn prev_greater_or_equal<T>(x: &[T]) -> Vec<Option<usize>>
+where
+    T: PartialOrd,
+{
+    prev_index_by(x, |a, b| a >= b)
+}
+
+#[snippet("monotone_stack")]
+/// 各iについて、x[j] < x[i]を満たす最小のj > i
+pub fn next_smaller<T>(x: &[T]) -> Vec<Option<usize>>
+where
+    T: PartialOrd,
+{
+    next_index_by(x, |a, b| a < b)
+}
+
+#[snippet("monotone_stack")]
+/// 各iについて、x[j] <= x[i]を満たす最小のj > i
+pub fn next_smaller_or_equal<T>(x: &[T]) -> Vec<Option<usize>>
+where
+    T: PartialOrd,
+{
+    next_index_by(x, |a, b| a <= b)
+}
+
+#[snippet("monotone_stack")]
+/// 各iについて、x[j] > x[i]を満たす最小のj > i
+pub fn next_greater<T>(x: &[T]) -> Vec<Option<usize>>
+where
+    T: PartialOrd,
+{
+    next_index_by(x, |a, b| a > b)
+}
+
+#[snippet("monotone_stack")]
+/// 各iについて、x[j] >= x[i]を満たす最小のj > i
+pub fn next_greater_or_equal<T>(x: &[T]) -> Vec<Option<usize>>
+where
+    T: PartialOrd,
+{
+    next_index_by(x, |a, b| a >= b)
+}
+
+#[snippet("cartesian-tree")]
+/// 最小値を根とするCartesian Tree. 同じ値の場合は左にある方を祖先とする. インデックスは0-origin
+pub struct CartesianTree {
+    pub root: Option<usize>,
+    pub parent: Vec<Option<usize>>,
+    pub left: Vec<Option<usize>>,
+    pub right: Vec<Option<usize>>,
+}
+
+#[snippet("cartesian-tree")]
+impl CartesianTree {
+    pub fn new<T>(x: &[T]) -> Self
+    where
+        T: PartialOrd,
+    {
+        let n = x.len();
+        let mut parent = vec![None; n];
+        let mut left = vec![None; n];
+        let mut right = vec![None; n];
+        // 右端の経路
+        let mut stack: Vec<usize> = vec![];
+        for i in 0..n {
+            let mut last = None;
+            while let Some(&j) = stack.last() {
+                if x[j] <= x[i] {
+                    break;
+                }
+                last = stack.pop();
+            }
+            // 取り除いた経路はiの左の子になる
+            if let Some(c) = last {
+                parent[c] = Some(i);
+                left[i] = Some(c);
+            }
+            if let Some(&j) = stack.last() {
+                parent[i] = Some(j);
+                right[j] = Some(i);
+            }
+            stack.push(i);
+        }
+        Self {
+            root: stack.first().copied(),
+            parent,
+            left,
+            right,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+
+    #[test]
+    fn test_monotone_stack() {
+        let x = [3, 1, 4, 1, 5, 9, 2, 6];
+        assert_eq!(
+            prev_smaller(&x),
+            vec![
+                None,
+                None,
+                Some(1),
+                None,
+                Some(3),
+                Some(4),
+                Some(3),
+                Some(6)
+            ]
+        );
+        assert_eq!(
+            next_greater(&x),
+            vec![
+                Some(2),
+                Some(2),
+                Some(4),
+                Some(4),
+                Some(5),
+                None,
+                Some(7),
+                None
+            ]
+        );
+
+        // 全探索と一致すること
+        let mut rng = Rng::default();
+        let x: Vec<u64> = (0..50).map(|_| rng.next_below(5)).collect();
+        let prev = |f: fn(u64, u64) -> bool| {
+            (0..x.len())
+                .map(|i| (0..i).rev().find(|&j| f(x[j], x[i])))
+                .collect::<Vec<_>>()
+        };
+        let next = |f: fn(u64, u64) -> bool| {
+            (0..x.len())
+                .map(|i| ((i + 1)..x.len()).find(|&j| f(x[j], x[i])))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(prev_smaller(&x), prev(|a, b| a < b));
+        assert_eq!(prev_smaller_or_equal(&x), prev(|a, b| a <= b));
+        assert_eq!(prev_greater(&x), prev(|a, b| a > b));
+        assert_eq!(prev_greater_or_equal(&x), prev(|a, b| a >= b));
+        assert_eq!(next_smaller(&x), next(|a, b| a < b));
+        assert_eq!(next_smaller_or_equal(&x), next(|a, b| a <= b));
+        assert_eq!(next_greater(&x), next(|a, b| a > b));
+        assert_eq!(next_greater_or_equal(&x), next(|a, b| a >= b));
+
+        // 空
+        assert!(prev_smaller::<i32>(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_largest_rectangle() {
+        // ヒストグラム中の最大の長方形
+        let h = [2, 1, 4, 5, 1, 3, 3];
+        let l = prev_smaller(&h);
+        let r = next_smaller(&h);
+        let res = (0..h.len())
+            .map(|i| {
+                let w = r[i].unwrap_or(h.len()) - l[i].map_or(0, |j| j + 1);
+                h[i] * w
+            })
+            .max();
+        assert_eq!(res, Some(8));
+    }
+
+    #[test]
+    fn test_cartesian_tree() {
+        let x = [5, 2, 8, 2, 6, 1, 7];
+        let tree = CartesianTree::new(&x);
+        assert_eq!(tree.root, Some(5));
+        assert_eq!(
+            tree.parent,
+            vec![Some(1), Some(5), Some(3), Some(1), Some(3), None, Some(5)]
+        );
+        assert_eq!(
+            tree.left,
+            vec![None, Some(0), None, Some(2), None, Some(1), None]
+        );
+        assert_eq!(
+            tree.right,
+            vec![None, Some(3), None, Some(4), None, Some(6), None]
+        );
+
+        // 中間順の走査が元の順序になり、親の値が子の値以下であること
+        let mut rng = Rng::default();
+        let x: Vec<u64> = (0..100).map(|_| rng.next_below(10)).collect();
+        let CartesianTree {
+            root,
+            parent,
+            left,
+            right,
+        } = CartesianTree::new(&x);
+        let mut order = vec![];
+        let mut stack = vec![];
+        let mut cur = root;
+        while cur.is_some() || !stack.is_empty() {
+            while let Some(v) = cur {
+                stack.push(v);
+                cur = left[v];
+            }
+            let v = stack.pop().unwrap();
+            order.push(v);
+            cur = right[v];
+        }
+        assert_eq!(order, (0..x.len()).collect::<Vec<_>>());
+        for i in 0..x.len() {
+            if let Some(p) = parent[i] {
+                assert!(x[p] <= x[i]);
+            }
+        }
+
+        // 空
+        let tree = CartesianTree::new::<i32>(&[]);
+        assert_eq!(tree.root, None);
+        assert!(tree.parent.is_empty());
+    }
+}
@@ -0,0 +1,336 @@
+use crate::cumulative_sum::{range_to_pair, Additive, Monoid};
+use cargo_snippet::snippet;
+
+#[snippet("persistent-segment-tree")]
+#[snippet(include = "monoid")]
+#[snippet(include = "range_to_pair")]
+struct PersistentNode<S> {
+    val: S,
+    // 子ノードの番号. 葉の場合は使わない
+    left: usize,
+    right: usize,
+}
+
+#[snippet("persistent-segment-tree")]
+/// 永続セグメント木. 更新のたびに新しい版の根を返し、過去の版に対しても区間取得ができる. インデックスは0-origin
+pub struct PersistentSegTree<M>
+where
+    M: Monoid,
+{
+    n: usize,
+    // 全ての版のノード
+    nodes: Vec<PersistentNode<M::S>>,
+}
+
+#[snippet("persistent-segment-tree")]
+impl<M> PersistentSegTree<M>
+where
+    M: Monoid,
+{
+    pub fn new(n: usize) -> Self {
+        Self { n, nodes: vec![] }
+    }
+
+    fn push_node(&mut self, val: M::S, left: usize, right: usize) -> usize {
+        self.nodes.push(PersistentNode { val, left, right });
+        self.nodes.len() - 1
+    }
+
+    /// 初期状態を構築し、その根を返す
+    pub fn build(&mut self, v: &[M::S]) -> usize {
+        assert_eq!(v.len(), self.n);
+        self.build_rec(v, 0, self.n)
+    }
+
+    fn build_rec(&mut self, v: &[M::S], l: usize, r: usize) -> usize {
+        if r - l <= 1 {
+            let val = if l < r { v[l].clone() } else { M::identity() };
+            return self.push_node(val, 0, 0);
+        }
+        let m = (l + r) / 2;
+        let left = self.build_rec(v, l, m);
+        let right = self.build_rec(v, m, r);
+        let val = M::op(&self.nodes[left].val, &self.nodes[right].val);
+        self.push_node(val, left, right)
+    }
+
+    /// 版rootのp番目の要素をxにした新しい版の根を返す
+    pub fn set(&mut self, root: usize, p: usize, x: M::S) -> usize {
+        assert!(p < self.n);
+        self.set_rec(root, p, x, 0, self.n)
+    }
+
+    fn set_rec(&mut self, k: usize, p: usize, x: M::S, l: usize, r: usize) -> usize {
+        if r - l == 1 {
+            return self.push_node(x, 0, 0);
+        }
+        let m = (l + r) / 2;
+        let (mut left, mut right) = (self.nodes[k].left, self.nodes[k].right);
+        // 更新する葉までの経路だけを複製する
+        if p < m {
+            left = self.set_rec(left, p, x, l, m);
+        } else {
+            right = self.set_rec(right, p, x, m, r);
+        }
+        let val = M::op(&self.nodes[left].val, &self.nodes[right].val);
+        self.push_node(val, left, right)
+    }
+
+    /// 版rootのp番目の要素を取得する
+    pub fn get(&self, root: usize, p: usize) -> M::S {
+        assert!(p < self.n);
+        let (mut k, mut l, mut r) = (root, 0, self.n);
+        while r - l > 1 {
+            let m = (l + r) / 2;
+            if p < m {
+                k = self.nodes[k].left;
+                r = m;
+            } else {
+                k = self.nodes[k].right;
+                l = m;
+            }
+        }
+        self.nodes[k].val.clone()
+    }
+
+    /// 版rootの範囲内の要素の総積を求める
+    pub fn prod<R>(&self, root: usize, range: R) -> M::S
+    where
+        R: std::ops::RangeBounds<usize>,
+    {
+        let (a, b) = range_to_pair(range, self.n);
+        if a == b {
+            return M::identity();
+        }
+        self.prod_rec(root, a, b, 0, self.n)
+    }
+
+    fn prod_rec(&self, k: usize, a: usize, b: usize, l: usize, r: usize) -> M::S {
+        if b <= l || r <= a {
+            return M::identity();
+        }
+        if a <= l && r <= b {
+            return self.nodes[k].val.clone();
+        }
+        let m = (l + r) / 2;
+        let vl = self.prod_rec(self.nodes[k].left, a, b, l, m);
+        let vr = self.prod_rec(self.nodes[k].right, a, b, m, r);
+        M::op(&vl, &vr)
+    }
+}
+
+#[snippet("persistent-segment-tree-kth")]
+#[snippet(include = "persistent-segment-tree")]
+#[snippet(include = "Additive")]
+impl PersistentSegTree<Additive<usize>> {
+    /// 各要素を値の出現回数とみなし、版root_rから版root_lを引いた多重集合でk番目（0-origin）に小さい値を返す
+    /// 各版が接頭辞における出現回数を持つとき、区間のk番目に小さい値になる. 要素数がk以下の場合はNone
+    pub fn kth(&self, root_l: usize, root_r: usize, k: usize) -> Option<usize> {
+        let (mut a, mut b) = (root_l, root_r);
+        if k >= self.nodes[b].val - self.nodes[a].val {
+            return None;
+        }
+        let (mut l, mut r, mut k) = (0, self.n, k);
+        while r - l > 1 {
+            let m = (l + r) / 2;
+            let (al, bl) = (self.nodes[a].left, self.nodes[b].left);
+            let c = self.nodes[bl].val - self.nodes[al].val;
+            if k < c {
+                a = al;
+                b = bl;
+                r = m;
+            } else {
+                k -= c;
+                a = self.nodes[a].right;
+                b = self.nodes[b].right;
+                l = m;
+            }
+        }
+        Some(l)
+    }
+}
+
+#[snippet("persistent-array")]
+enum PersistentArrayNode<T> {
+    Leaf(T),
+    // 左右の子ノードの番号
+    Inner(usize, usize),
+}
+
+#[snippet("persistent-array")]
+/// 永続配列. 更新のたびに新しい版の根を返す. 値は葉にのみ持つ. インデックスは0-origin
+pub struct PersistentArray<T>
+where
+    T: Clone,
+{
+    n: usize,
+    // 全ての版のノード
+    nodes: Vec<PersistentArrayNode<T>>,
+}
+
+#[snippet("persistent-array")]
+impl<T> PersistentArray<T>
+where
+    T: Clone,
+{
+    pub fn new(n: usize) -> Self {
+        Self { n, nodes: vec![] }
+    }
+
+    fn push_node(&mut self, node: PersistentArrayNode<T>) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    /// 初期状態を構築し、その根を返す
+    pub fn build(&mut self, v: &[T]) -> usize {
+        assert_eq!(v.len(), self.n);
+        if self.n == 0 {
+            return self.push_node(PersistentArrayNode::Inner(0, 0));
+        }
+        self.build_rec(v, 0, self.n)
+    }
+
+    fn build_rec(&mut self, v: &[T], l: usize, r: usize) -> usize {
+        if r - l == 1 {
+            return self.push_node(PersistentArrayNode::Leaf(v[l].clone()));
+        }
+        let m = (l + r) / 2;
+        let left = self.build_rec(v, l, m);
+        let right = self.build_rec(v, m, r);
+        self.push_node(PersistentArrayNode::Inner(left, right))
+    }
+
+    /// 版rootのp番目の要素をxにした新しい版の根を返す
+    pub fn set(&mut self, root: usize, p: usize, x: T) -> usize {
+        assert!(p < self.n);
+        self.set_rec(root, p, x, 0, self.n)
+    }
+
+    fn set_rec(&mut self, k: usize, p: usize, x: T, l: usize, r: usize) -> usize {
+        if r - l == 1 {
+            return self.push_node(PersistentArrayNode::Leaf(x));
+        }
+        let m = (l + r) / 2;
+        let (mut left, mut right) = match self.nodes[k] {
+            PersistentArrayNode::Inner(left, right) => (left, right),
+            PersistentArrayNode::Leaf(_) => unreachable!(),
+        };
+        // 更新する葉までの経路だけを複製する
+        if p < m {
+            left = self.set_rec(left, p, x, l, m);
+        } else {
+            right = self.set_rec(right, p, x, m, r);
+        }
+        self.push_node(PersistentArrayNode::Inner(left, right))
+    }
+
+    /// 版rootのp番目の要素を取得する
+    pub fn get(&self, root: usize, p: usize) -> &T {
+        assert!(p < self.n);
+        let (mut k, mut l, mut r) = (root, 0, self.n);
+        loop {
+            match &self.nodes[k] {
+                PersistentArrayNode::Leaf(x) => return x,
+                &PersistentArrayNode::Inner(left, right) => {
+                    let m = (l + r) / 2;
+                    if p < m {
+                        k = left;
+                        r = m;
+                    } else {
+                        k = right;
+                        l = m;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cumulative_sum::Max;
+
+    #[test]
+    fn test_persistent_segment_tree() {
+        let mut st: PersistentSegTree<Max<i64>> = PersistentSegTree::new(5);
+        let r0 = st.build(&[3, 1, 4, 1, 5]);
+        let r1 = st.set(r0, 1, 10);
+        let r2 = st.set(r1, 4, 0);
+        let r3 = st.set(r0, 2, -1);
+        // 過去の版が変更されないこと
+        assert_eq!(st.prod(r0, ..), 5);
+        assert_eq!(st.prod(r0, 0..2), 3);
+        assert_eq!(st.prod(r1, 0..2), 10);
+        assert_eq!(st.prod(r2, 2..), 4);
+        assert_eq!(st.prod(r3, 1..4), 1);
+        assert_eq!(st.prod(r3, 2..2), i64::MIN);
+        assert_eq!(st.get(r0, 1), 1);
+        assert_eq!(st.get(r2, 1), 10);
+        assert_eq!(st.get(r2, 4), 0);
+    }
+
+    #[test]
+    fn test_persistent_segment_tree_kth() {
+        // 各接頭辞における値の出現回数を版として持ち、区間のk番目に小さい値を求める
+        let v: Vec<usize> = vec![3, 1, 4, 1, 5, 9, 2, 6, 5, 3];
+        let m = 10;
+        let mut st: PersistentSegTree<Additive<usize>> = PersistentSegTree::new(m);
+        let mut roots = vec![st.build(&vec![0; m])];
+        for &x in v.iter() {
+            let root = *roots.last().unwrap();
+            let c = st.get(root, x);
+            roots.push(st.set(root, x, c + 1));
+        }
+        // 全探索と一致すること
+        for l in 0..=v.len() {
+            for r in l..=v.len() {
+                let mut sorted = v[l..r].to_vec();
+                sorted.sort();
+                for (k, &x) in sorted.iter().enumerate() {
+                    assert_eq!(st.kth(roots[l], roots[r], k), Some(x));
+                }
+                assert_eq!(st.kth(roots[l], roots[r], r - l), None);
+            }
+        }
+
+        // 要素数1
+        let mut st: PersistentSegTree<Additive<usize>> = PersistentSegTree::new(1);
+        let r0 = st.build(&[0]);
+        let r1 = st.set(r0, 0, 2);
+        assert_eq!(st.kth(r0, r1, 1), Some(0));
+        assert_eq!(st.kth(r0, r1, 2), None);
+    }
+
+    #[test]
+    fn test_persistent_array() {
+        let mut arr: PersistentArray<char> = PersistentArray::new(3);
+        let r0 = arr.build(&['a', 'b', 'c']);
+        let r1 = arr.set(r0, 0, 'x');
+        let r2 = arr.set(r1, 2, 'y');
+        let r3 = arr.set(r0, 2, 'z');
+        let to_string = |root| (0..3).map(|i| *arr.get(root, i)).collect::<String>();
+        assert_eq!(to_string(r0), "abc");
+        assert_eq!(to_string(r1), "xbc");
+        assert_eq!(to_string(r2), "xby");
+        assert_eq!(to_string(r3), "abz");
+
+        // 要素数1
+        let mut arr: PersistentArray<i32> = PersistentArray::new(1);
+        let r0 = arr.build(&[1]);
+        let r1 = arr.set(r0, 0, 2);
+        assert_eq!(arr.get(r0, 0), &1);
+        assert_eq!(arr.get(r1, 0), &2);
+
+        // 複製のコストが大きい要素
+        let mut arr: PersistentArray<Vec<i32>> = PersistentArray::new(4);
+        let r0 = arr.build(&vec![vec![]; 4]);
+        let r1 = arr.set(r0, 3, vec![1, 2, 3]);
+        assert!(arr.get(r0, 3).is_empty());
+        assert_eq!(arr.get(r1, 3), &vec![1, 2, 3]);
+        // 空
+        let mut arr: PersistentArray<i32> = PersistentArray::new(0);
+        arr.build(&[]);
+    }
+}
@@ -0,0 +1,255 @@
+use crate::cumulative_sum::{range_to_pair, Monoid};
+use cargo_snippet::snippet;
+
+#[snippet("segment-tree")]
+#[snippet(include = "monoid")]
+#[snippet(include = "range_to_pair")]
+/// セグメント木. 1点更新・区間取得を行う. インデックスは0-origin
+pub struct SegTree<M>
+where
+    M: Monoid,
+{
+    n: usize,
+    // 葉の数（n以上の最小の2冪）
+    size: usize,
+    log: u32,
+    // 1-originの完全二分木. 葉はdata[size..]
+    data: Vec<M::S>,
+}
+
+#[snippet("segment-tree")]
+impl<M> SegTree<M>
+where
+    M: Monoid,
+{
+    pub fn new(n: usize) -> Self {
+        Self::from_vec(vec![M::identity(); n])
+    }
+
+    pub fn from_vec(v: Vec<M::S>) -> Self {
+        let n = v.len();
+        let size = n.next_power_of_two();
+        let log = size.trailing_zeros();
+        let mut data = vec![M::identity(); 2 * size];
+        for (i, x) in v.into_iter().enumerate() {
+            data[size + i] = x;
+        }
+        let mut st = Self { n, size, log, data };
+        for i in (1..size).rev() {
+            st.update(i);
+        }
+        st
+    }
+
+    fn update(&mut self, k: usize) {
+        self.data[k] = M::op(&self.data[2 * k], &self.data[2 * k + 1]);
+    }
+
+    /// p番目の要素をxにする
+    pub fn set(&mut self, p: usize, x: M::S) {
+        assert!(p < self.n);
+        let p = p + self.size;
+        self.data[p] = x;
+        for i in 1..=self.log {
+            self.update(p >> i);
+        }
+    }
+
+    /// p番目の要素を取得する
+    pub fn get(&self, p: usize) -> M::S {
+        assert!(p < self.n);
+        self.data[p + self.size].clone()
+    }
+
+    /// 範囲内の要素の総積を求める
+    pub fn prod<R>(&self, range: R) -> M::S
+    where
+        R: std::ops::RangeBounds<usize>,
+    {
+        let (l, r) = range_to_pair(range, self.n);
+        let mut sml = M::identity();
+        let mut smr = M::identity();
+        let mut l = l + self.size;
+        let mut r = r + self.size;
+        while l < r {
+            if l & 1 == 1 {
+                sml = M::op(&sml, &self.data[l]);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                smr = M::op(&self.data[r], &smr);
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+        M::op(&sml, &smr)
+    }
+
+    /// 全要素の総積を求める
+    pub fn all_prod(&self) -> M::S {
+        self.data[1].clone()
+    }
+
+    /// f(prod(l..r)) = true となる最大のrを返す
+    /// fは単調（trueとなる区間の部分区間もtrue）で、f(単位元) = true であること
+    pub fn max_right<F>(&self, l: usize, f: F) -> usize
+    where
+        F: Fn(&M::S) -> bool,
+    {
+        assert!(l <= self.n);
+        assert!(f(&M::identity()));
+        if l == self.n {
+            return self.n;
+        }
+        let mut l = l + self.size;
+        let mut sm = M::identity();
+        loop {
+            while l & 1 == 0 {
+                l >>= 1;
+            }
+            if !f(&M::op(&sm, &self.data[l])) {
+                // 条件を満たさなくなる葉まで降りる
+                while l < self.size {
+                    l *= 2;
+                    let res = M::op(&sm, &self.data[l]);
+                    if f(&res) {
+                        sm = res;
+                        l += 1;
+                    }
+                }
+                return l - self.size;
+            }
+            sm = M::op(&sm, &self.data[l]);
+            l += 1;
+            if l & l.wrapping_neg() == l {
+                return self.n;
+            }
+        }
+    }
+
+    /// f(prod(l..r)) = true となる最小のlを返す
+    /// fは単調（trueとなる区間の部分区間もtrue）で、f(単位元) = true であること
+    pub fn min_left<F>(&self, r: usize, f: F) -> usize
+    where
+        F: Fn(&M::S) -> bool,
+    {
+        assert!(r <= self.n);
+        assert!(f(&M::identity()));
+        if r == 0 {
+            return 0;
+        }
+        let mut r = r + self.size;
+        let mut sm = M::identity();
+        loop {
+            r -= 1;
+            while r > 1 && r & 1 == 1 {
+                r >>= 1;
+            }
+            if !f(&M::op(&self.data[r], &sm)) {
+                // 条件を満たさなくなる葉まで降りる
+                while r < self.size {
+                    r = 2 * r + 1;
+                    let res = M::op(&self.data[r], &sm);
+                    if f(&res) {
+                        sm = res;
+                        r -= 1;
+                    }
+                }
+                return r + 1 - self.size;
+            }
+            sm = M::op(&self.data[r], &sm);
+            if r & r.wrapping_neg() == r {
+                return 0;
+            }
+        }
+    }
+}
+
+#[snippet("Affine")]
+#[snippet(include = "monoid")]
+/// 一次関数 x -> ax + b の合成のモノイド. 要素は(a, b)で表す
+/// op(f, g) はfを適用した後にgを適用する関数
+pub struct Affine<T>(std::marker::PhantomData<T>);
+
+#[snippet("Affine")]
+impl<T> Monoid for Affine<T>
+where
+    T: num::Num + Copy,
+{
+    type S = (T, T);
+    fn identity() -> (T, T) {
+        (T::one(), T::zero())
+    }
+    fn op(f: &(T, T), g: &(T, T)) -> (T, T) {
+        (g.0 * f.0, g.0 * f.1 + g.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cumulative_sum::{Additive, Max, Min};
+
+    #[test]
+    fn test_segment_tree() {
+        let v: Vec<i64> = vec![5, 3, 7, 9, 6, 4, 1, 2];
+        let mut st: SegTree<Min<i64>> = SegTree::from_vec(v);
+        assert_eq!(st.prod(..), 1);
+        assert_eq!(st.prod(0..3), 3);
+        assert_eq!(st.prod(2..=4), 6);
+        assert_eq!(st.prod(3..3), i64::MAX);
+        st.set(1, 10);
+        assert_eq!(st.get(1), 10);
+        assert_eq!(st.prod(0..3), 5);
+        assert_eq!(st.all_prod(), 1);
+
+        // 要素数が2冪でない場合
+        let mut st: SegTree<Additive<i64>> = SegTree::new(5);
+        for i in 0..5 {
+            st.set(i, i as i64 + 1);
+        }
+        assert_eq!(st.prod(..), 15);
+        assert_eq!(st.prod(1..4), 9);
+        assert_eq!(st.all_prod(), 15);
+
+        // 空
+        let st: SegTree<Max<i32>> = SegTree::new(0);
+        assert_eq!(st.prod(..), i32::MIN);
+    }
+
+    #[test]
+    fn test_segment_tree_affine() {
+        // 関数の合成は非可換なので順序が保たれること
+        let v: Vec<(i64, i64)> = vec![(2, 1), (3, 0), (1, -4), (5, 2)];
+        let st: SegTree<Affine<i64>> = SegTree::from_vec(v);
+        // x -> 2x + 1 -> 3(2x + 1) = 6x + 3
+        assert_eq!(st.prod(0..2), (6, 3));
+        // x -> 3x -> 3x - 4 -> 15x - 18
+        assert_eq!(st.prod(1..4), (15, -18));
+        assert_eq!(st.prod(2..2), (1, 0));
+    }
+
+    #[test]
+    fn test_segment_tree_max_right_min_left() {
+        let v: Vec<i64> = vec![1, 2, 3, 4, 5, 6, 7];
+        let st: SegTree<Additive<i64>> = SegTree::from_vec(v.clone());
+        // 全探索と一致すること
+        for x in 0..30 {
+            for l in 0..=v.len() {
+                let expected = (l..=v.len())
+                    .filter(|&r| v[l..r].iter().sum::<i64>() <= x)
+                    .max()
+                    .unwrap();
+                assert_eq!(st.max_right(l, |&s| s <= x), expected);
+            }
+            for r in 0..=v.len() {
+                let expected = (0..=r)
+                    .filter(|&l| v[l..r].iter().sum::<i64>() <= x)
+                    .min()
+                    .unwrap();
+                assert_eq!(st.min_left(r, |&s| s <= x), expected);
+            }
+        }
+    }
+}
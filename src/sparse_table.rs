@@ -0,0 +1,201 @@
+use crate::cumulative_sum::range_to_pair;
+use crate::math::{max, min};
+use cargo_snippet::snippet;
+
+#[snippet("sparse-table")]
+#[snippet(include = "range_to_pair")]
+#[snippet(include = "min")]
+#[snippet(include = "max")]
+/// Sparse Table. 冪等な演算（min, max, gcdなど）の区間取得をO(1)で行う. インデックスは0-origin
+pub struct SparseTable<T> {
+    // table[k][i] = [i, i + 2^k) の演算結果
+    table: Vec<Vec<T>>,
+    op: fn(T, T) -> T,
+}
+
+#[snippet("sparse-table")]
+impl<T> SparseTable<T>
+where
+    T: Copy,
+{
+    /// opは結合則を満たし、冪等（op(a, a) = a）であること
+    pub fn new(v: &[T], op: fn(T, T) -> T) -> Self {
+        let mut table = vec![v.to_vec()];
+        let mut k = 1;
+        while (1 << k) <= v.len() {
+            let prev = &table[k - 1];
+            let half = 1 << (k - 1);
+            let row = (0..=(v.len() - (1 << k)))
+                .map(|i| op(prev[i], prev[i + half]))
+                .collect();
+            table.push(row);
+            k += 1;
+        }
+        Self { table, op }
+    }
+
+    pub fn len(&self) -> usize {
+        self.table[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table[0].is_empty()
+    }
+
+    /// 範囲内の要素の演算結果を求める. 範囲は空でないこと
+    pub fn query<R>(&self, range: R) -> T
+    where
+        R: std::ops::RangeBounds<usize>,
+    {
+        let (l, r) = range_to_pair(range, self.len());
+        assert!(l < r, "range must not be empty");
+        // 区間を長さ2^kの2つの区間で覆う
+        let k = (usize::BITS - 1 - (r - l).leading_zeros()) as usize;
+        (self.op)(self.table[k][l], self.table[k][r - (1 << k)])
+    }
+}
+
+#[snippet("sparse-table")]
+impl<T> SparseTable<T>
+where
+    T: Copy + PartialOrd,
+{
+    /// 区間最小値を求めるSparse Tableを作る
+    pub fn new_min(v: &[T]) -> Self {
+        Self::new(v, min)
+    }
+
+    /// 区間最大値を求めるSparse Tableを作る
+    pub fn new_max(v: &[T]) -> Self {
+        Self::new(v, max)
+    }
+}
+
+#[snippet("disjoint-sparse-table")]
+#[snippet(include = "range_to_pair")]
+/// Disjoint Sparse Table. 結合則を満たす任意の演算の区間取得をO(1)で行う. インデックスは0-origin
+pub struct DisjointSparseTable<T> {
+    data: Vec<T>,
+    // table[k]: 2^kの倍数（奇数倍）を境界として、境界から左右に向かって演算した結果
+    table: Vec<Vec<T>>,
+    op: fn(T, T) -> T,
+}
+
+#[snippet("disjoint-sparse-table")]
+impl<T> DisjointSparseTable<T>
+where
+    T: Copy,
+{
+    /// opは結合則を満たすこと
+    pub fn new(v: &[T], op: fn(T, T) -> T) -> Self {
+        let n = v.len();
+        let mut table = vec![];
+        let mut k = 0;
+        while (1 << k) < n {
+            let half = 1 << k;
+            let mut row = v.to_vec();
+            for mid in (half..n).step_by(2 * half) {
+                // 境界の左側は右から左へ、右側は左から右へ演算する
+                for i in ((mid - half)..(mid - 1)).rev() {
+                    row[i] = op(v[i], row[i + 1]);
+                }
+                for i in (mid + 1)..(mid + half).min(n) {
+                    row[i] = op(row[i - 1], v[i]);
+                }
+            }
+            table.push(row);
+            k += 1;
+        }
+        Self {
+            data: v.to_vec(),
+            table,
+            op,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// 範囲内の要素の演算結果を求める. 範囲は空でないこと
+    pub fn query<R>(&self, range: R) -> T
+    where
+        R: std::ops::RangeBounds<usize>,
+    {
+        let (l, r) = range_to_pair(range, self.len());
+        assert!(l < r, "range must not be empty");
+        let r = r - 1;
+        if l == r {
+            return self.data[l];
+        }
+        // l と r が初めて異なるブロックに分かれる段
+        let k = (usize::BITS - 1 - (l ^ r).leading_zeros()) as usize;
+        (self.op)(self.table[k][l], self.table[k][r])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::gcd;
+
+    #[test]
+    fn test_sparse_table() {
+        let v: Vec<i64> = vec![5, 3, 7, 9, 6, 4, 1, 2, 8];
+        let st_min = SparseTable::new_min(&v);
+        let st_max = SparseTable::new_max(&v);
+        // 全探索と一致すること
+        for l in 0..v.len() {
+            for r in (l + 1)..=v.len() {
+                assert_eq!(st_min.query(l..r), *v[l..r].iter().min().unwrap());
+                assert_eq!(st_max.query(l..r), *v[l..r].iter().max().unwrap());
+            }
+        }
+        assert_eq!(st_min.query(..), 1);
+        assert_eq!(st_max.query(2..=3), 9);
+
+        // gcd
+        let st = SparseTable::new(&[12u64, 18, 24, 9, 7], gcd);
+        assert_eq!(st.query(0..3), 6);
+        assert_eq!(st.query(0..4), 3);
+        assert_eq!(st.query(4..5), 7);
+
+        // 要素数1
+        let st = SparseTable::new_min(&[1]);
+        assert_eq!(st.query(..), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sparse_table_panic() {
+        // 空の範囲
+        SparseTable::new_min(&[1, 2, 3]).query(1..1);
+    }
+
+    #[test]
+    fn test_disjoint_sparse_table() {
+        let v: Vec<i64> = vec![3, 1, 4, 1, 5, 9, 2, 6, 5, 3, 5];
+        let dst = DisjointSparseTable::new(&v, |a, b| a + b);
+        // 全探索と一致すること
+        for l in 0..v.len() {
+            for r in (l + 1)..=v.len() {
+                assert_eq!(dst.query(l..r), v[l..r].iter().sum::<i64>());
+            }
+        }
+
+        // 非可換な演算（文字列の連結を桁で表現）
+        let v: Vec<u64> = vec![1, 2, 3, 4, 5];
+        let dst =
+            DisjointSparseTable::new(&v, |a, b| a * 10u64.pow(b.to_string().len() as u32) + b);
+        assert_eq!(dst.query(..), 12345);
+        assert_eq!(dst.query(1..4), 234);
+
+        // 要素数1
+        let dst = DisjointSparseTable::new(&[7], |a, b| a + b);
+        assert_eq!(dst.query(..), 7);
+    }
+}
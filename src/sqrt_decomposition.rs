@@ -0,0 +1,299 @@
+use crate::cumulative_sum::range_to_pair;
+use cargo_snippet::snippet;
+
+#[snippet("sqrt-decomposition")]
+#[snippet(include = "range_to_pair")]
+/// 平方分割の各ブロックの振る舞い
+pub trait SqrtBlock {
+    /// 要素
+    type T: Clone;
+    /// ブロック全体に遅延して作用させる写像
+    type F: Clone;
+    /// ブロックごとに前計算する情報
+    type Info;
+    /// 恒等写像
+    fn identity_map() -> Self::F;
+    /// fをxに作用させる
+    fn mapping(f: &Self::F, x: &Self::T) -> Self::T;
+    /// gを作用させた後にfを作用させる写像
+    fn composition(f: &Self::F, g: &Self::F) -> Self::F;
+    /// ブロックの要素から情報を構築する. 要素が変更されるたびに呼ばれる
+    fn build(v: &[Self::T]) -> Self::Info;
+}
+
+#[snippet("sqrt-decomposition")]
+/// 平方分割. 区間作用はブロック単位の遅延評価で、区間取得はブロックごとの情報を使って行う. インデックスは0-origin
+pub struct SqrtDecomposition<B>
+where
+    B: SqrtBlock,
+{
+    n: usize,
+    // ブロックの幅
+    width: usize,
+    // 各ブロックの遅延評価前の要素
+    data: Vec<B::T>,
+    // ブロック全体に作用させていない写像
+    lazy: Vec<B::F>,
+    infos: Vec<B::Info>,
+}
+
+#[snippet("sqrt-decomposition")]
+impl<B> SqrtDecomposition<B>
+where
+    B: SqrtBlock,
+{
+    /// ブロックの幅を√nとして構築する
+    pub fn new(v: Vec<B::T>) -> Self {
+        let width = ((v.len() as f64).sqrt().ceil() as usize).max(1);
+        Self::with_width(v, width)
+    }
+
+    /// ブロックの幅を指定して構築する
+    pub fn with_width(v: Vec<B::T>, width: usize) -> Self {
+        assert!(width > 0);
+        let n = v.len();
+        let infos = v.chunks(width).map(B::build).collect::<Vec<_>>();
+        let lazy = vec![B::identity_map(); infos.len()];
+        Self {
+            n,
+            width,
+            data: v,
+            lazy,
+            infos,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    // b番目のブロックが表す区間
+    fn block_range(&self, b: usize) -> (usize, usize) {
+        (b * self.width, ((b + 1) * self.width).min(self.n))
+    }
+
+    // b番目のブロックの写像を要素に反映する
+    fn push(&mut self, b: usize) {
+        let (l, r) = self.block_range(b);
+        let f = std::mem::replace(&mut self.lazy[b], B::identity_map());
+        for x in self.data[l..r].iter_mut() {
+            *x = B::mapping(&f, x);
+        }
+    }
+
+    // b番目のブロックの情報を再構築する
+    fn rebuild(&mut self, b: usize) {
+        let (l, r) = self.block_range(b);
+        self.infos[b] = B::build(&self.data[l..r]);
+    }
+
+    pub fn get(&self, p: usize) -> B::T {
+        assert!(p < self.n);
+        B::mapping(&self.lazy[p / self.width], &self.data[p])
+    }
+
+    pub fn set(&mut self, p: usize, x: B::T) {
+        assert!(p < self.n);
+        let b = p / self.width;
+        self.push(b);
+        self.data[p] = x;
+        self.rebuild(b);
+    }
+
+    /// 範囲内の要素にfを作用させる
+    pub fn apply<R>(&mut self, range: R, f: B::F)
+    where
+        R: std::ops::RangeBounds<usize>,
+    {
+        let (l, r) = range_to_pair(range, self.n);
+        if l == r {
+            return;
+        }
+        for b in (l / self.width)..=((r - 1) / self.width) {
+            let (bl, br) = self.block_range(b);
+            if l <= bl && br <= r {
+                self.lazy[b] = B::composition(&f, &self.lazy[b]);
+            } else {
+                // 一部だけが含まれるブロックは要素を直接更新する
+                self.push(b);
+                for x in self.data[bl.max(l)..br.min(r)].iter_mut() {
+                    *x = B::mapping(&f, x);
+                }
+                self.rebuild(b);
+            }
+        }
+    }
+
+    /// 範囲内の要素を畳み込む
+    /// 全体が含まれるブロックではfull(acc, 情報, 写像)を、一部だけが含まれるブロックでは各要素についてsingle(acc, 要素)を呼ぶ
+    pub fn fold<R, A, G, H>(&self, range: R, init: A, mut full: G, mut single: H) -> A
+    where
+        R: std::ops::RangeBounds<usize>,
+        G: FnMut(A, &B::Info, &B::F) -> A,
+        H: FnMut(A, B::T) -> A,
+    {
+        let (l, r) = range_to_pair(range, self.n);
+        let mut acc = init;
+        if l == r {
+            return acc;
+        }
+        for b in (l / self.width)..=((r - 1) / self.width) {
+            let (bl, br) = self.block_range(b);
+            if l <= bl && br <= r {
+                acc = full(acc, &self.infos[b], &self.lazy[b]);
+            } else {
+                for x in self.data[bl.max(l)..br.min(r)].iter() {
+                    acc = single(acc, B::mapping(&self.lazy[b], x));
+                }
+            }
+        }
+        acc
+    }
+}
+
+#[snippet("RangeAddCountGe")]
+#[snippet(include = "sqrt-decomposition")]
+/// 区間加算と、区間内のx以上の要素の数の取得を行うブロック. 各ブロックはソート済みの要素を持つ
+pub struct AddSorted<T>(std::marker::PhantomData<T>);
+
+#[snippet("RangeAddCountGe")]
+impl<T> SqrtBlock for AddSorted<T>
+where
+    T: num::Num + PartialOrd + Copy,
+{
+    type T = T;
+    type F = T;
+    type Info = Vec<T>;
+    fn identity_map() -> T {
+        T::zero()
+    }
+    fn mapping(f: &T, x: &T) -> T {
+        *x + *f
+    }
+    fn composition(f: &T, g: &T) -> T {
+        *f + *g
+    }
+    fn build(v: &[T]) -> Vec<T> {
+        let mut sorted = v.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        sorted
+    }
+}
+
+#[snippet("RangeAddCountGe")]
+/// 区間加算・区間内のx以上の要素の数の取得を行う平方分割
+pub type RangeAddCountGe<T> = SqrtDecomposition<AddSorted<T>>;
+
+#[snippet("RangeAddCountGe")]
+impl<T> SqrtDecomposition<AddSorted<T>>
+where
+    T: num::Num + PartialOrd + Copy,
+{
+    /// 範囲内のx以上の要素の数
+    pub fn count_ge<R>(&self, range: R, x: T) -> usize
+    where
+        R: std::ops::RangeBounds<usize>,
+    {
+        self.fold(
+            range,
+            0,
+            |acc, sorted, &add| acc + sorted.len() - sorted.partition_point(|&y| y + add < x),
+            |acc, y| acc + (y >= x) as usize,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+
+    #[test]
+    fn test_range_add_count_ge() {
+        let mut v: Vec<i64> = vec![3, 1, 4, 1, 5, 9, 2, 6, 5, 3, 5];
+        let mut sd: RangeAddCountGe<i64> = SqrtDecomposition::new(v.clone());
+        assert_eq!(sd.len(), 11);
+        assert_eq!(sd.count_ge(.., 5), 5);
+        assert_eq!(sd.count_ge(2..2, 0), 0);
+
+        // 全探索と一致すること
+        let mut rng = Rng::default();
+        for _ in 0..300 {
+            let (l, r) = rng.range(v.len());
+            let k = rng.next_below(21) as i64 - 10;
+            match rng.next_below(3) {
+                0 => {
+                    sd.apply(l..r, k);
+                    for y in v[l..r].iter_mut() {
+                        *y += k;
+                    }
+                }
+                1 => {
+                    if l < v.len() {
+                        sd.set(l, k);
+                        v[l] = k;
+                    }
+                }
+                _ => {
+                    let expected = v[l..r].iter().filter(|&&y| y >= k).count();
+                    assert_eq!(sd.count_ge(l..r, k), expected);
+                }
+            }
+            for (i, &y) in v.iter().enumerate() {
+                assert_eq!(sd.get(i), y);
+            }
+        }
+    }
+
+    // 区間に値を代入し、区間和を求めるブロック
+    struct AssignSum;
+
+    impl SqrtBlock for AssignSum {
+        type T = i64;
+        type F = Option<i64>;
+        // (要素の和, 要素数)
+        type Info = (i64, usize);
+        fn identity_map() -> Option<i64> {
+            None
+        }
+        fn mapping(f: &Option<i64>, x: &i64) -> i64 {
+            f.unwrap_or(*x)
+        }
+        fn composition(f: &Option<i64>, g: &Option<i64>) -> Option<i64> {
+            f.or(*g)
+        }
+        fn build(v: &[i64]) -> (i64, usize) {
+            (v.iter().sum(), v.len())
+        }
+    }
+
+    #[test]
+    fn test_sqrt_decomposition() {
+        let mut sd: SqrtDecomposition<AssignSum> = SqrtDecomposition::with_width(vec![1; 10], 3);
+        let sum = |sd: &SqrtDecomposition<AssignSum>, l: usize, r: usize| {
+            sd.fold(
+                l..r,
+                0,
+                |acc, &(s, len), f| acc + f.map_or(s, |x| x * len as i64),
+                |acc, x| acc + x,
+            )
+        };
+        assert_eq!(sum(&sd, 0, 10), 10);
+        sd.apply(2..9, Some(5));
+        assert_eq!(sum(&sd, 0, 10), 2 + 35 + 1);
+        sd.apply(3..6, Some(0));
+        sd.set(4, 100);
+        assert_eq!(sum(&sd, 0, 10), 2 + 5 + 100 + 15 + 1);
+        assert_eq!(sum(&sd, 4, 5), 100);
+        assert_eq!(sd.get(8), 5);
+
+        // 空
+        let sd: SqrtDecomposition<AssignSum> = SqrtDecomposition::new(vec![]);
+        assert!(sd.is_empty());
+        assert_eq!(sum(&sd, 0, 0), 0);
+    }
+}
@@ -28,7 +28,7 @@ pub struct Parenthesis {
 #[snippet("Parenthesis")]
 impl Parenthesis {
     // TODO 必要が出てきたらジェネリクスにする
-    pub fn new(left_sign: char, right_sign: char, data: &Vec<char>) -> Self {
+    pub fn new(left_sign: char, right_sign: char, data: &[char]) -> Self {
         let d: Vec<i8> = data
             .iter()
             .map(|&x| if x == left_sign { 1 } else { -1 })
@@ -78,13 +78,13 @@ mod tests {
     fn test_join_to_string() {
         // ベクタ
         // 文字列
-        assert_eq!(vec!["a", "b", "c"].iter().join_to_string(" "), "a b c");
+        assert_eq!(["a", "b", "c"].iter().join_to_string(" "), "a b c");
         // 数値
-        assert_eq!(vec![0, 1, 2].iter().join_to_string("."), "0.1.2");
+        assert_eq!([0, 1, 2].iter().join_to_string("."), "0.1.2");
         // 空ベクタ
         assert_eq!(Vec::<i32>::new().iter().join_to_string(","), "");
         // 要素数1
-        assert_eq!(vec!["a"].iter().join_to_string(" "), "a");
+        assert_eq!(["a"].iter().join_to_string(" "), "a");
 
         // 配列
         let arr = [0, 1, 2, 3];
@@ -0,0 +1,39 @@
+//! テスト用の補助関数
+
+/// テスト用の疑似乱数（xorshift）
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 7;
+        self.0 ^= self.0 >> 9;
+        self.0
+    }
+
+    /// [0, m)の値
+    pub fn next_below(&mut self, m: u64) -> u64 {
+        self.next_u64() % m
+    }
+
+    /// [0, m)の値
+    pub fn next_usize(&mut self, m: usize) -> usize {
+        self.next_below(m as u64) as usize
+    }
+
+    /// 0 <= l <= r <= nとなる区間[l, r)
+    pub fn range(&mut self, n: usize) -> (usize, usize) {
+        let a = self.next_below(n as u64 + 1) as usize;
+        let b = self.next_below(n as u64 + 1) as usize;
+        (a.min(b), a.max(b))
+    }
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Self::new(88172645463325252)
+    }
+}
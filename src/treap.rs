@@ -0,0 +1,665 @@
+use crate::cumulative_sum::{range_to_pair, Monoid};
+use cargo_snippet::snippet;
+
+#[snippet("ordered-multiset")]
+struct MultisetNode<T> {
+    val: T,
+    // 優先度. 親の優先度は子の優先度以上
+    pri: u64,
+    // 部分木の要素数
+    size: usize,
+    left: Option<Box<MultisetNode<T>>>,
+    right: Option<Box<MultisetNode<T>>>,
+}
+
+#[snippet("ordered-multiset")]
+impl<T> MultisetNode<T> {
+    fn new(val: T, pri: u64) -> Self {
+        Self {
+            val,
+            pri,
+            size: 1,
+            left: None,
+            right: None,
+        }
+    }
+
+    fn size(t: &Option<Box<Self>>) -> usize {
+        t.as_ref().map_or(0, |n| n.size)
+    }
+
+    fn update(&mut self) {
+        self.size = 1 + Self::size(&self.left) + Self::size(&self.right);
+    }
+
+    // aの全要素がbの全要素以下であること
+    fn merge(a: Option<Box<Self>>, b: Option<Box<Self>>) -> Option<Box<Self>> {
+        match (a, b) {
+            (None, b) => b,
+            (a, None) => a,
+            (Some(mut a), Some(mut b)) => {
+                if a.pri > b.pri {
+                    a.right = Self::merge(a.right.take(), Some(b));
+                    a.update();
+                    Some(a)
+                } else {
+                    b.left = Self::merge(Some(a), b.left.take());
+                    b.update();
+                    Some(b)
+                }
+            }
+        }
+    }
+
+    // f(要素) = true となる要素とfalseとなる要素に分割する. fは単調（true → false）であること
+    fn split<F>(t: Option<Box<Self>>, f: &F) -> (Option<Box<Self>>, Option<Box<Self>>)
+    where
+        F: Fn(&T) -> bool,
+    {
+        match t {
+            None => (None, None),
+            Some(mut n) => {
+                if f(&n.val) {
+                    let (l, r) = Self::split(n.right.take(), f);
+                    n.right = l;
+                    n.update();
+                    (Some(n), r)
+                } else {
+                    let (l, r) = Self::split(n.left.take(), f);
+                    n.left = r;
+                    n.update();
+                    (l, Some(n))
+                }
+            }
+        }
+    }
+}
+
+#[snippet("ordered-multiset")]
+/// 順序つき多重集合（Treap）. k番目の要素や順位をO(log n)で求める
+pub struct OrderedMultiset<T> {
+    root: Option<Box<MultisetNode<T>>>,
+    // 優先度を生成する乱数（xorshift）の状態
+    seed: u64,
+}
+
+#[snippet("ordered-multiset")]
+impl<T> OrderedMultiset<T>
+where
+    T: Ord,
+{
+    pub fn new() -> Self {
+        Self {
+            root: None,
+            seed: 88172645463325252,
+        }
+    }
+
+    fn next_priority(&mut self) -> u64 {
+        self.seed ^= self.seed << 7;
+        self.seed ^= self.seed >> 9;
+        self.seed
+    }
+
+    // 分割後の木の乱数の種. 元の木と同じ優先度の列を生成しないよう、定数とのxorを取ってから1回進める
+    fn split_seed(&mut self) -> u64 {
+        let mut seed = self.next_priority() ^ 0x9e37_79b9_7f4a_7c15;
+        if seed == 0 {
+            seed = 88172645463325252;
+        }
+        seed ^= seed << 7;
+        seed ^= seed >> 9;
+        seed
+    }
+
+    pub fn len(&self) -> usize {
+        MultisetNode::size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// xを追加する
+    pub fn insert(&mut self, x: T) {
+        let pri = self.next_priority();
+        let (l, r) = MultisetNode::split(self.root.take(), &|v| v < &x);
+        let node = Some(Box::new(MultisetNode::new(x, pri)));
+        self.root = MultisetNode::merge(MultisetNode::merge(l, node), r);
+    }
+
+    /// xを1つ削除する. 削除できた場合はtrueを返す
+    pub fn erase_one(&mut self, x: &T) -> bool {
+        let (l, r) = MultisetNode::split(self.root.take(), &|v| v < x);
+        let (m, r) = MultisetNode::split(r, &|v| v <= x);
+        let removed = m.is_some();
+        // xと等しい要素からなる部分木の根を取り除く
+        let m = m.and_then(|mut n| MultisetNode::merge(n.left.take(), n.right.take()));
+        self.root = MultisetNode::merge(MultisetNode::merge(l, m), r);
+        removed
+    }
+
+    /// f(要素) = true となる要素の数. fは単調（true → false）であること
+    fn count_by<F>(&self, f: F) -> usize
+    where
+        F: Fn(&T) -> bool,
+    {
+        let mut t = &self.root;
+        let mut res = 0;
+        while let Some(n) = t {
+            if f(&n.val) {
+                res += MultisetNode::size(&n.left) + 1;
+                t = &n.right;
+            } else {
+                t = &n.left;
+            }
+        }
+        res
+    }
+
+    /// xより小さい要素の数
+    pub fn rank(&self, x: &T) -> usize {
+        self.count_by(|v| v < x)
+    }
+
+    /// xと等しい要素の数
+    pub fn count(&self, x: &T) -> usize {
+        self.count_by(|v| v <= x) - self.rank(x)
+    }
+
+    pub fn contains(&self, x: &T) -> bool {
+        self.count(x) > 0
+    }
+
+    /// k番目（0-origin）に小さい要素
+    pub fn kth(&self, k: usize) -> Option<&T> {
+        let mut t = &self.root;
+        let mut k = k;
+        while let Some(n) = t {
+            let ls = MultisetNode::size(&n.left);
+            if k < ls {
+                t = &n.left;
+            } else if k == ls {
+                return Some(&n.val);
+            } else {
+                k -= ls + 1;
+                t = &n.right;
+            }
+        }
+        None
+    }
+
+    /// x以上の最小の要素
+    pub fn lower_bound(&self, x: &T) -> Option<&T> {
+        self.kth(self.rank(x))
+    }
+
+    /// xより大きい最小の要素
+    pub fn upper_bound(&self, x: &T) -> Option<&T> {
+        self.kth(self.count_by(|v| v <= x))
+    }
+
+    /// x以上の要素を取り除き、それらからなる多重集合を返す
+    pub fn split_off(&mut self, x: &T) -> Self {
+        let (l, r) = MultisetNode::split(self.root.take(), &|v| v < x);
+        self.root = l;
+        Self {
+            root: r,
+            seed: self.split_seed(),
+        }
+    }
+
+    /// otherの全要素を追加する. selfの全要素がotherの全要素以下であること
+    pub fn merge(&mut self, other: Self) {
+        if let (Some(a), Some(b)) = (self.kth(self.len().wrapping_sub(1)), other.kth(0)) {
+            assert!(
+                a <= b,
+                "all elements of self must not be greater than other"
+            );
+        }
+        self.root = MultisetNode::merge(self.root.take(), other.root);
+    }
+}
+
+#[snippet("ordered-multiset")]
+impl<T> Default for OrderedMultiset<T>
+where
+    T: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[snippet("implicit-treap")]
+#[snippet(include = "monoid")]
+#[snippet(include = "range_to_pair")]
+struct ImplicitNode<M>
+where
+    M: Monoid,
+{
+    val: M::S,
+    // 部分木の総積
+    sum: M::S,
+    // 部分木を反転したときの総積
+    rev_sum: M::S,
+    // 優先度. 親の優先度は子の優先度以上
+    pri: u64,
+    // 部分木の要素数
+    size: usize,
+    // 子に伝播していない反転操作があるか
+    rev: bool,
+    left: Option<Box<ImplicitNode<M>>>,
+    right: Option<Box<ImplicitNode<M>>>,
+}
+
+#[snippet("implicit-treap")]
+impl<M> ImplicitNode<M>
+where
+    M: Monoid,
+{
+    fn new(val: M::S, pri: u64) -> Self {
+        Self {
+            sum: val.clone(),
+            rev_sum: val.clone(),
+            val,
+            pri,
+            size: 1,
+            rev: false,
+            left: None,
+            right: None,
+        }
+    }
+
+    fn size(t: &Option<Box<Self>>) -> usize {
+        t.as_ref().map_or(0, |n| n.size)
+    }
+
+    fn update(&mut self) {
+        self.size = 1 + Self::size(&self.left) + Self::size(&self.right);
+        let (l, lr) = match &self.left {
+            Some(n) => (n.sum.clone(), n.rev_sum.clone()),
+            None => (M::identity(), M::identity()),
+        };
+        let (r, rr) = match &self.right {
+            Some(n) => (n.sum.clone(), n.rev_sum.clone()),
+            None => (M::identity(), M::identity()),
+        };
+        self.sum = M::op(&M::op(&l, &self.val), &r);
+        self.rev_sum = M::op(&M::op(&rr, &self.val), &lr);
+    }
+
+    // 部分木を反転させる
+    fn toggle(&mut self) {
+        std::mem::swap(&mut self.sum, &mut self.rev_sum);
+        self.rev ^= true;
+    }
+
+    fn push(&mut self) {
+        if self.rev {
+            std::mem::swap(&mut self.left, &mut self.right);
+            if let Some(n) = self.left.as_mut() {
+                n.toggle();
+            }
+            if let Some(n) = self.right.as_mut() {
+                n.toggle();
+            }
+            self.rev = false;
+        }
+    }
+
+    fn merge(a: Option<Box<Self>>, b: Option<Box<Self>>) -> Option<Box<Self>> {
+        match (a, b) {
+            (None, b) => b,
+            (a, None) => a,
+            (Some(mut a), Some(mut b)) => {
+                if a.pri > b.pri {
+                    a.push();
+                    a.right = Self::merge(a.right.take(), Some(b));
+                    a.update();
+                    Some(a)
+                } else {
+                    b.push();
+                    b.left = Self::merge(Some(a), b.left.take());
+                    b.update();
+                    Some(b)
+                }
+            }
+        }
+    }
+
+    // 先頭k個とそれ以外に分割する
+    fn split(t: Option<Box<Self>>, k: usize) -> (Option<Box<Self>>, Option<Box<Self>>) {
+        match t {
+            None => (None, None),
+            Some(mut n) => {
+                n.push();
+                let ls = Self::size(&n.left);
+                if k <= ls {
+                    let (l, r) = Self::split(n.left.take(), k);
+                    n.left = r;
+                    n.update();
+                    (l, Some(n))
+                } else {
+                    let (l, r) = Self::split(n.right.take(), k - ls - 1);
+                    n.right = l;
+                    n.update();
+                    (Some(n), r)
+                }
+            }
+        }
+    }
+
+    fn collect(t: &Option<Box<Self>>, rev: bool, res: &mut Vec<M::S>) {
+        if let Some(n) = t {
+            let rev = rev ^ n.rev;
+            let (first, second) = if rev {
+                (&n.right, &n.left)
+            } else {
+                (&n.left, &n.right)
+            };
+            Self::collect(first, rev, res);
+            res.push(n.val.clone());
+            Self::collect(second, rev, res);
+        }
+    }
+}
+
+#[snippet("implicit-treap")]
+/// 列を管理する平衡二分木（暗黙のキーを持つTreap）. 挿入・削除・区間反転・区間取得をO(log n)で行う. インデックスは0-origin
+pub struct ImplicitTreap<M>
+where
+    M: Monoid,
+{
+    root: Option<Box<ImplicitNode<M>>>,
+    // 優先度を生成する乱数（xorshift）の状態
+    seed: u64,
+}
+
+#[snippet("implicit-treap")]
+impl<M> ImplicitTreap<M>
+where
+    M: Monoid,
+{
+    pub fn new() -> Self {
+        Self {
+            root: None,
+            seed: 88172645463325252,
+        }
+    }
+
+    pub fn from_vec(v: Vec<M::S>) -> Self {
+        let mut t = Self::new();
+        for x in v {
+            let pri = t.next_priority();
+            let node = Some(Box::new(ImplicitNode::new(x, pri)));
+            t.root = ImplicitNode::merge(t.root.take(), node);
+        }
+        t
+    }
+
+    fn next_priority(&mut self) -> u64 {
+        self.seed ^= self.seed << 7;
+        self.seed ^= self.seed >> 9;
+        self.seed
+    }
+
+    // 分割後の列の乱数の種. 元の列と同じ優先度の列を生成しないよう、定数とのxorを取ってから1回進める
+    fn split_seed(&mut self) -> u64 {
+        let mut seed = self.next_priority() ^ 0x9e37_79b9_7f4a_7c15;
+        if seed == 0 {
+            seed = 88172645463325252;
+        }
+        seed ^= seed << 7;
+        seed ^= seed >> 9;
+        seed
+    }
+
+    pub fn len(&self) -> usize {
+        ImplicitNode::size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// pos番目にvを挿入する
+    pub fn insert(&mut self, pos: usize, v: M::S) {
+        assert!(pos <= self.len());
+        let pri = self.next_priority();
+        let (l, r) = ImplicitNode::split(self.root.take(), pos);
+        let node = Some(Box::new(ImplicitNode::new(v, pri)));
+        self.root = ImplicitNode::merge(ImplicitNode::merge(l, node), r);
+    }
+
+    /// pos番目の要素を削除して返す
+    pub fn erase(&mut self, pos: usize) -> M::S {
+        assert!(pos < self.len());
+        let (l, r) = ImplicitNode::split(self.root.take(), pos);
+        let (m, r) = ImplicitNode::split(r, 1);
+        self.root = ImplicitNode::merge(l, r);
+        m.unwrap().val
+    }
+
+    /// pos番目の要素
+    pub fn get(&mut self, pos: usize) -> M::S {
+        assert!(pos < self.len());
+        self.fold(pos..=pos)
+    }
+
+    /// 先頭at個を残し、それ以降の要素からなる列を返す
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len());
+        let (l, r) = ImplicitNode::split(self.root.take(), at);
+        self.root = l;
+        Self {
+            root: r,
+            seed: self.split_seed(),
+        }
+    }
+
+    /// 末尾にotherを連結する
+    pub fn merge(&mut self, other: Self) {
+        self.root = ImplicitNode::merge(self.root.take(), other.root);
+    }
+
+    /// 範囲内の要素を反転する
+    pub fn reverse<R>(&mut self, range: R)
+    where
+        R: std::ops::RangeBounds<usize>,
+    {
+        let (l, r) = range_to_pair(range, self.len());
+        let (a, b) = ImplicitNode::split(self.root.take(), l);
+        let (mut b, c) = ImplicitNode::split(b, r - l);
+        if let Some(n) = b.as_mut() {
+            n.toggle();
+        }
+        self.root = ImplicitNode::merge(ImplicitNode::merge(a, b), c);
+    }
+
+    /// 範囲内の要素の総積を求める
+    pub fn fold<R>(&mut self, range: R) -> M::S
+    where
+        R: std::ops::RangeBounds<usize>,
+    {
+        let (l, r) = range_to_pair(range, self.len());
+        let (a, b) = ImplicitNode::split(self.root.take(), l);
+        let (b, c) = ImplicitNode::split(b, r - l);
+        let res = b.as_ref().map_or(M::identity(), |n| n.sum.clone());
+        self.root = ImplicitNode::merge(ImplicitNode::merge(a, b), c);
+        res
+    }
+
+    /// 全要素を順に並べたベクタ
+    pub fn to_vec(&self) -> Vec<M::S> {
+        let mut res = Vec::with_capacity(self.len());
+        ImplicitNode::collect(&self.root, false, &mut res);
+        res
+    }
+}
+
+#[snippet("implicit-treap")]
+impl<M> Default for ImplicitTreap<M>
+where
+    M: Monoid,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cumulative_sum::{Additive, Min};
+    use crate::segment_tree::Affine;
+
+    fn to_vec(s: &OrderedMultiset<i64>) -> Vec<i64> {
+        (0..s.len()).map(|k| *s.kth(k).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_ordered_multiset() {
+        let mut s = OrderedMultiset::new();
+        assert!(s.is_empty());
+        for x in [5, 1, 4, 1, 5, 9, 2, 6, 5] {
+            s.insert(x);
+        }
+        assert_eq!(s.len(), 9);
+        assert_eq!(to_vec(&s), vec![1, 1, 2, 4, 5, 5, 5, 6, 9]);
+        assert_eq!(s.count(&5), 3);
+        assert_eq!(s.count(&3), 0);
+        assert!(s.contains(&9));
+        assert_eq!(s.rank(&5), 4);
+        assert_eq!(s.rank(&0), 0);
+        assert_eq!(s.rank(&10), 9);
+        assert_eq!(s.kth(8), Some(&9));
+        assert_eq!(s.kth(9), None);
+        assert_eq!(s.lower_bound(&3), Some(&4));
+        assert_eq!(s.lower_bound(&5), Some(&5));
+        assert_eq!(s.lower_bound(&10), None);
+        assert_eq!(s.upper_bound(&5), Some(&6));
+        assert_eq!(s.upper_bound(&0), Some(&1));
+        assert_eq!(s.upper_bound(&9), None);
+
+        // 1つだけ削除されること
+        assert!(s.erase_one(&5));
+        assert_eq!(s.count(&5), 2);
+        assert!(!s.erase_one(&3));
+        assert_eq!(to_vec(&s), vec![1, 1, 2, 4, 5, 5, 6, 9]);
+    }
+
+    #[test]
+    fn test_ordered_multiset_split_merge() {
+        let mut s = OrderedMultiset::new();
+        for x in 0..100 {
+            s.insert(x % 10);
+        }
+        let mut t = s.split_off(&4);
+        assert_eq!(s.len(), 40);
+        assert_eq!(t.len(), 60);
+        assert_eq!(s.kth(39), Some(&3));
+        assert_eq!(t.kth(0), Some(&4));
+        let mut u = t.split_off(&100);
+        assert!(u.is_empty());
+        // 分割後の木は元の木と異なる優先度の列を使う
+        let p: Vec<u64> = (0..10).map(|_| t.next_priority()).collect();
+        let q: Vec<u64> = (0..10).map(|_| u.next_priority()).collect();
+        assert!(p.iter().all(|x| !q.contains(x)));
+        s.merge(t);
+        assert_eq!(s.len(), 100);
+        assert_eq!(s.rank(&7), 70);
+        // 空の集合とのマージ
+        s.merge(OrderedMultiset::new());
+        assert_eq!(s.len(), 100);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_ordered_multiset_merge_panic() {
+        let mut s = OrderedMultiset::new();
+        s.insert(5);
+        let mut t = OrderedMultiset::new();
+        t.insert(3);
+        s.merge(t);
+    }
+
+    #[test]
+    fn test_ordered_multiset_large() {
+        // 昇順に挿入しても偏らないこと
+        let mut s = OrderedMultiset::new();
+        for x in 0..100000 {
+            s.insert(x);
+        }
+        assert_eq!(s.kth(50000), Some(&50000));
+        for x in (0..100000).step_by(2) {
+            assert!(s.erase_one(&x));
+        }
+        assert_eq!(s.len(), 50000);
+        assert_eq!(s.kth(0), Some(&1));
+        assert_eq!(s.rank(&50001), 25000);
+    }
+
+    #[test]
+    fn test_implicit_treap() {
+        let mut t: ImplicitTreap<Additive<i64>> = ImplicitTreap::from_vec(vec![1, 2, 3, 4, 5]);
+        assert_eq!(t.len(), 5);
+        assert_eq!(t.fold(..), 15);
+        assert_eq!(t.fold(1..4), 9);
+        t.insert(2, 10);
+        assert_eq!(t.to_vec(), vec![1, 2, 10, 3, 4, 5]);
+        t.insert(6, 20);
+        t.insert(0, 30);
+        assert_eq!(t.to_vec(), vec![30, 1, 2, 10, 3, 4, 5, 20]);
+        assert_eq!(t.erase(2), 2);
+        assert_eq!(t.get(2), 10);
+        assert_eq!(t.to_vec(), vec![30, 1, 10, 3, 4, 5, 20]);
+        assert_eq!(t.fold(2..2), 0);
+
+        // 分割と連結
+        let mut u = t.split_off(3);
+        assert_eq!(t.to_vec(), vec![30, 1, 10]);
+        assert_eq!(u.to_vec(), vec![3, 4, 5, 20]);
+        // 分割後の列は元の列と異なる優先度の列を使う
+        let p: Vec<u64> = (0..10).map(|_| t.next_priority()).collect();
+        let q: Vec<u64> = (0..10).map(|_| u.next_priority()).collect();
+        assert!(p.iter().all(|x| !q.contains(x)));
+        u.merge(t);
+        assert_eq!(u.to_vec(), vec![3, 4, 5, 20, 30, 1, 10]);
+        assert_eq!(u.fold(2..5), 55);
+    }
+
+    #[test]
+    fn test_implicit_treap_reverse() {
+        // 非可換な演算で反転後の総積が正しいこと
+        let v: Vec<(i64, i64)> = (1..=8).map(|i| (i, i * 10)).collect();
+        let mut t: ImplicitTreap<Affine<i64>> = ImplicitTreap::from_vec(v.clone());
+        let mut w = v.clone();
+        for (l, r) in [(0, 8), (2, 5), (1, 7), (3, 4), (0, 3), (5, 8)] {
+            t.reverse(l..r);
+            w[l..r].reverse();
+            assert_eq!(t.to_vec(), w);
+            for a in 0..=8 {
+                for b in a..=8 {
+                    let expected = w[a..b]
+                        .iter()
+                        .fold((1, 0), |acc, f| (f.0 * acc.0, f.0 * acc.1 + f.1));
+                    assert_eq!(t.fold(a..b), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_implicit_treap_large() {
+        let n = 100000;
+        let mut t: ImplicitTreap<Min<i64>> = ImplicitTreap::new();
+        for i in 0..n {
+            t.insert(i as usize, i);
+        }
+        t.reverse(..);
+        assert_eq!(t.get(0), n - 1);
+        assert_eq!(t.fold(..10), n - 10);
+        assert_eq!(t.fold(..), 0);
+    }
+}
@@ -1,28 +1,42 @@
+use crate::cumulative_sum::Monoid;
 use cargo_snippet::snippet;
 
 #[snippet("union-find")]
 /// Union-Find木. 頂点番号は1-origin
 pub struct UnionFind {
-    parents: Vec<usize>,
+    // 経路圧縮を&selfで行うためCellで持つ
+    parents: Vec<std::cell::Cell<usize>>,
     sizes: Vec<usize>,
+    // グループの数
+    count: usize,
 }
 
 #[snippet("union-find")]
 impl UnionFind {
     pub fn new(size: usize) -> Self {
         Self {
-            parents: vec![0; size + 1], // 0: 親なし
+            parents: vec![std::cell::Cell::new(0); size + 1], // 0: 親なし
             sizes: vec![1; size + 1],
+            count: size,
         }
     }
 
-    // uが属するグループの根を求める
-    fn root(&self, u: usize) -> usize {
-        let par = self.parents[u];
-        if par == 0 {
-            return u;
+    /// uが属するグループの根を求める
+    pub fn root(&self, u: usize) -> usize {
+        let mut r = u;
+        while self.parents[r].get() != 0 {
+            r = self.parents[r].get();
         }
-        self.root(par)
+        // 経路圧縮
+        let mut v = u;
+        while v != r {
+            let par = self.parents[v].get();
+            if par != r {
+                self.parents[v].set(r);
+            }
+            v = par;
+        }
+        r
     }
 
     // u, v が同じグループに属しているか
@@ -37,36 +51,806 @@ impl UnionFind {
         if root_u == root_v {
             return;
         }
+        self.count -= 1;
+        // Union by size
+        if self.sizes[root_u] > self.sizes[root_v] {
+            self.parents[root_v].set(root_u);
+            self.sizes[root_u] += self.sizes[root_v];
+        } else {
+            self.parents[root_u].set(root_v);
+            self.sizes[root_v] += self.sizes[root_u];
+        }
+    }
+
+    /// uが属するグループの頂点数
+    pub fn size(&self, u: usize) -> usize {
+        self.sizes[self.root(u)]
+    }
+
+    /// グループの数
+    pub fn group_count(&self) -> usize {
+        self.count
+    }
+
+    /// 各グループに属する頂点の一覧. グループは最小の頂点番号の順、頂点は昇順に並ぶ
+    pub fn groups(&self) -> Vec<Vec<usize>> {
+        let n = self.parents.len() - 1;
+        // index[r]: 根rのグループの番号
+        let mut index = vec![usize::MAX; n + 1];
+        let mut res: Vec<Vec<usize>> = Vec::with_capacity(self.count);
+        for u in 1..=n {
+            let r = self.root(u);
+            if index[r] == usize::MAX {
+                index[r] = res.len();
+                res.push(Vec::with_capacity(self.sizes[r]));
+            }
+            res[index[r]].push(u);
+        }
+        res
+    }
+}
+
+#[snippet("weighted-union-find")]
+/// ポテンシャル付きUnion-Find木. x_v - x_u = w の形の制約を管理する. 頂点番号は1-origin
+/// Tは加法について可換群をなすこと
+pub struct WeightedUnionFind<T = i64> {
+    // 経路圧縮で親と重みを付け替えるため、UnionFindと同様にCellで持つ
+    parents: Vec<std::cell::Cell<usize>>,
+    sizes: Vec<usize>,
+    // weights[u] = x_u - x_(uの親)
+    weights: Vec<std::cell::Cell<T>>,
+}
+
+#[snippet("weighted-union-find")]
+impl<T> WeightedUnionFind<T>
+where
+    T: num::Zero + std::ops::Sub<Output = T> + PartialEq + Copy,
+{
+    pub fn new(size: usize) -> Self {
+        Self {
+            parents: vec![std::cell::Cell::new(0); size + 1], // 0: 親なし
+            sizes: vec![1; size + 1],
+            weights: vec![std::cell::Cell::new(T::zero()); size + 1],
+        }
+    }
+
+    // uが属するグループの根と、x_u - x_(根)を求める
+    fn root(&self, u: usize) -> (usize, T) {
+        let mut r = u;
+        let mut w = T::zero();
+        while self.parents[r].get() != 0 {
+            w = w + self.weights[r].get();
+            r = self.parents[r].get();
+        }
+        // 経路圧縮. accは経路上の頂点vについてのx_v - x_(根)
+        let mut v = u;
+        let mut acc = w;
+        while v != r {
+            let par = self.parents[v].get();
+            let wv = self.weights[v].get();
+            self.parents[v].set(r);
+            self.weights[v].set(acc);
+            acc = acc - wv;
+            v = par;
+        }
+        (r, w)
+    }
+
+    // u, v が同じグループに属しているか
+    pub fn is_same(&self, u: usize, v: usize) -> bool {
+        self.root(u).0 == self.root(v).0
+    }
+
+    /// x_v - x_u = w という制約を追加する. 既存の制約と矛盾する場合はfalseを返す
+    pub fn unite(&mut self, u: usize, v: usize, w: T) -> bool {
+        let (root_u, wu) = self.root(u);
+        let (root_v, wv) = self.root(v);
+        if root_u == root_v {
+            return wv - wu == w;
+        }
+        // x_(root_v) - x_(root_u)
+        let d = w + wu - wv;
         // Union by size
         if self.sizes[root_u] > self.sizes[root_v] {
-            self.parents[root_v] = root_u;
+            self.parents[root_v].set(root_u);
             self.sizes[root_u] += self.sizes[root_v];
+            self.weights[root_v].set(d);
         } else {
-            self.parents[root_u] = root_v;
+            self.parents[root_u].set(root_v);
             self.sizes[root_v] += self.sizes[root_u];
+            self.weights[root_u].set(T::zero() - d);
+        }
+        true
+    }
+
+    /// u, v が同じグループに属している場合、x_v - x_u を返す
+    pub fn diff(&self, u: usize, v: usize) -> Option<T> {
+        let (root_u, wu) = self.root(u);
+        let (root_v, wv) = self.root(v);
+        if root_u == root_v {
+            Some(wv - wu)
+        } else {
+            None
+        }
+    }
+}
+
+#[snippet("rollback-union-find")]
+/// 操作を取り消せるUnion-Find木. 経路圧縮を行わないため各操作はO(log n). 頂点番号は1-origin
+pub struct RollbackUnionFind {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+    count: usize,
+    // 統合の履歴. (親を付け替えた根, 新しい親). 統合しなかった場合はNone
+    history: Vec<Option<(usize, usize)>>,
+    // snapshot時点の履歴の長さ
+    snapshots: Vec<usize>,
+}
+
+#[snippet("rollback-union-find")]
+impl RollbackUnionFind {
+    pub fn new(size: usize) -> Self {
+        Self {
+            parents: vec![0; size + 1], // 0: 親なし
+            sizes: vec![1; size + 1],
+            count: size,
+            history: vec![],
+            snapshots: vec![],
+        }
+    }
+
+    /// uが属するグループの根を求める
+    pub fn root(&self, u: usize) -> usize {
+        let mut r = u;
+        while self.parents[r] != 0 {
+            r = self.parents[r];
+        }
+        r
+    }
+
+    // u, v が同じグループに属しているか
+    pub fn is_same(&self, u: usize, v: usize) -> bool {
+        self.root(u) == self.root(v)
+    }
+
+    /// u, v の属するグループを統合する. 既に同じグループだった場合はfalseを返す
+    pub fn unite(&mut self, u: usize, v: usize) -> bool {
+        let mut root_u = self.root(u);
+        let mut root_v = self.root(v);
+        if root_u == root_v {
+            self.history.push(None);
+            return false;
+        }
+        // Union by size
+        if self.sizes[root_u] > self.sizes[root_v] {
+            std::mem::swap(&mut root_u, &mut root_v);
+        }
+        self.parents[root_u] = root_v;
+        self.sizes[root_v] += self.sizes[root_u];
+        self.count -= 1;
+        self.history.push(Some((root_u, root_v)));
+        true
+    }
+
+    /// 最後のuniteを取り消す
+    pub fn undo(&mut self) {
+        if let Some((c, p)) = self.history.pop().expect("no operation to undo") {
+            self.parents[c] = 0;
+            self.sizes[p] -= self.sizes[c];
+            self.count += 1;
+        }
+    }
+
+    /// 現在の状態を記録する. 入れ子にできる
+    pub fn snapshot(&mut self) {
+        self.snapshots.push(self.history.len());
+    }
+
+    /// 最後に記録した状態に戻す
+    pub fn rollback(&mut self) {
+        let len = self.snapshots.pop().expect("no snapshot");
+        while self.history.len() > len {
+            self.undo();
+        }
+    }
+
+    /// uが属するグループの頂点数
+    pub fn size(&self, u: usize) -> usize {
+        self.sizes[self.root(u)]
+    }
+
+    /// グループの数
+    pub fn group_count(&self) -> usize {
+        self.count
+    }
+}
+
+#[snippet("offline-dynamic-connectivity")]
+#[snippet(include = "rollback-union-find")]
+/// 辺の追加・削除と連結性の判定をオフラインで処理する. 頂点番号は1-origin
+/// 時間についてのセグメント木に各辺の存在区間を載せ、取り消せるUnion-Find木で走査する
+pub struct OfflineDynamicConnectivity {
+    n: usize,
+    // 未削除の辺の追加時刻（それまでのクエリ数）
+    open: std::collections::HashMap<(usize, usize), Vec<usize>>,
+    // (u, v, 追加時刻, 削除時刻)
+    edges: Vec<(usize, usize, usize, usize)>,
+    queries: Vec<(usize, usize)>,
+}
+
+#[snippet("offline-dynamic-connectivity")]
+impl OfflineDynamicConnectivity {
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            open: std::collections::HashMap::new(),
+            edges: vec![],
+            queries: vec![],
+        }
+    }
+
+    /// 辺u-vを追加する. 多重辺も可
+    pub fn link(&mut self, u: usize, v: usize) {
+        let key = (u.min(v), u.max(v));
+        self.open.entry(key).or_default().push(self.queries.len());
+    }
+
+    /// 辺u-vを1本削除する. 辺u-vが存在すること
+    pub fn cut(&mut self, u: usize, v: usize) {
+        let key = (u.min(v), u.max(v));
+        let l = self
+            .open
+            .get_mut(&key)
+            .and_then(|s| s.pop())
+            .expect("edge not found");
+        if l < self.queries.len() {
+            self.edges.push((key.0, key.1, l, self.queries.len()));
+        }
+    }
+
+    /// その時点でu, vが連結かを問うクエリを追加する
+    pub fn query(&mut self, u: usize, v: usize) {
+        self.queries.push((u, v));
+    }
+
+    /// 各クエリの答えを追加した順に返す
+    pub fn solve(&self) -> Vec<bool> {
+        let q = self.queries.len();
+        if q == 0 {
+            return vec![];
+        }
+        let size = q.next_power_of_two();
+        let mut seg: Vec<Vec<(usize, usize)>> = vec![vec![]; 2 * size];
+        let opened = self
+            .open
+            .iter()
+            .flat_map(|(&(u, v), s)| s.iter().map(move |&l| (u, v, l, q)));
+        for (u, v, l, r) in self.edges.iter().copied().chain(opened) {
+            let (mut l, mut r) = (l + size, r + size);
+            while l < r {
+                if l & 1 == 1 {
+                    seg[l].push((u, v));
+                    l += 1;
+                }
+                if r & 1 == 1 {
+                    r -= 1;
+                    seg[r].push((u, v));
+                }
+                l >>= 1;
+                r >>= 1;
+            }
+        }
+        let mut uf = RollbackUnionFind::new(self.n);
+        let mut res = vec![false; q];
+        self.dfs(1, size, &seg, &mut uf, &mut res);
+        res
+    }
+
+    fn dfs(
+        &self,
+        k: usize,
+        size: usize,
+        seg: &[Vec<(usize, usize)>],
+        uf: &mut RollbackUnionFind,
+        res: &mut [bool],
+    ) {
+        if k >= size && k - size >= self.queries.len() {
+            return;
         }
+        uf.snapshot();
+        for &(u, v) in seg[k].iter() {
+            uf.unite(u, v);
+        }
+        if k >= size {
+            let (u, v) = self.queries[k - size];
+            res[k - size] = uf.is_same(u, v);
+        } else {
+            self.dfs(2 * k, size, seg, uf, res);
+            self.dfs(2 * k + 1, size, seg, uf, res);
+        }
+        uf.rollback();
+    }
+}
+
+#[snippet("partially-persistent-union-find")]
+/// 部分永続Union-Find木. 過去の時刻の状態に対して判定ができる. 頂点番号は1-origin
+/// 時刻tはt回目のuniteを行った直後の状態を表す（初期状態は時刻0）
+pub struct PartiallyPersistentUnionFind {
+    parents: Vec<usize>,
+    // 親が設定された時刻. 根の場合はusize::MAX
+    times: Vec<usize>,
+    // 根であった間の頂点数の履歴. (時刻, 頂点数)
+    sizes: Vec<Vec<(usize, usize)>>,
+    now: usize,
+}
+
+#[snippet("partially-persistent-union-find")]
+impl PartiallyPersistentUnionFind {
+    pub fn new(size: usize) -> Self {
+        Self {
+            parents: vec![0; size + 1], // 0: 親なし
+            times: vec![usize::MAX; size + 1],
+            sizes: vec![vec![(0, 1)]; size + 1],
+            now: 0,
+        }
+    }
+
+    /// 現在の時刻（uniteを行った回数）
+    pub fn now(&self) -> usize {
+        self.now
+    }
+
+    /// 時刻tにおいてuが属するグループの根を求める
+    pub fn root_at(&self, u: usize, t: usize) -> usize {
+        let mut r = u;
+        while self.times[r] <= t {
+            r = self.parents[r];
+        }
+        r
+    }
+
+    /// u, v の属するグループを統合し、時刻を1進める. 既に同じグループだった場合はfalseを返す
+    pub fn unite(&mut self, u: usize, v: usize) -> bool {
+        self.now += 1;
+        let mut root_u = self.root_at(u, self.now);
+        let mut root_v = self.root_at(v, self.now);
+        if root_u == root_v {
+            return false;
+        }
+        // Union by size
+        let size_u = self.sizes[root_u].last().unwrap().1;
+        let size_v = self.sizes[root_v].last().unwrap().1;
+        if size_u > size_v {
+            std::mem::swap(&mut root_u, &mut root_v);
+        }
+        self.parents[root_u] = root_v;
+        self.times[root_u] = self.now;
+        self.sizes[root_v].push((self.now, size_u + size_v));
+        true
+    }
+
+    /// 時刻tにおいてu, v が同じグループに属しているか
+    pub fn is_same_at(&self, u: usize, v: usize, t: usize) -> bool {
+        self.root_at(u, t) == self.root_at(v, t)
+    }
+
+    /// 時刻tにおいてuが属するグループの頂点数
+    pub fn size_at(&self, u: usize, t: usize) -> usize {
+        let history = &self.sizes[self.root_at(u, t)];
+        let i = history.partition_point(|&(time, _)| time <= t);
+        history[i - 1].1
+    }
+
+    /// u, v が初めて同じグループに属した時刻. 現在も別のグループの場合はNone
+    pub fn first_connected_time(&self, u: usize, v: usize) -> Option<usize> {
+        if !self.is_same_at(u, v, self.now) {
+            return None;
+        }
+        if self.is_same_at(u, v, 0) {
+            return Some(0);
+        }
+        // is_same_at(u, v, ng)は偽、is_same_at(u, v, ok)は真
+        let (mut ng, mut ok) = (0, self.now);
+        while ok - ng > 1 {
+            let m = (ng + ok) / 2;
+            if self.is_same_at(u, v, m) {
+                ok = m;
+            } else {
+                ng = m;
+            }
+        }
+        Some(ok)
+    }
+}
+
+#[snippet("union-find-with-data")]
+#[snippet(include = "union-find")]
+#[snippet(include = "monoid")]
+/// グループごとのデータを持つUnion-Find木. 頂点番号は1-origin
+/// 統合時にはmerge(大きい方のグループのデータ, 小さい方のグループのデータ)を呼ぶ
+pub struct UnionFindWithData<T> {
+    uf: UnionFind,
+    // 根以外はNone
+    data: Vec<Option<T>>,
+    merge: fn(&mut T, T),
+}
+
+#[snippet("union-find-with-data")]
+impl<T> UnionFindWithData<T> {
+    /// 頂点iの初期データをdata[i - 1]として構築する
+    pub fn new(data: Vec<T>, merge: fn(&mut T, T)) -> Self {
+        let uf = UnionFind::new(data.len());
+        let data = std::iter::once(None)
+            .chain(data.into_iter().map(Some))
+            .collect();
+        Self { uf, data, merge }
+    }
+
+    /// モノイドの演算でデータを統合する
+    /// 統合の順序はグループの大きさで決まるため、演算は可換であること
+    pub fn with_monoid<M>(data: Vec<T>) -> Self
+    where
+        M: Monoid<S = T>,
+    {
+        Self::new(data, |a, b| *a = M::op(a, &b))
+    }
+
+    pub fn root(&self, u: usize) -> usize {
+        self.uf.root(u)
+    }
+
+    pub fn is_same(&self, u: usize, v: usize) -> bool {
+        self.uf.is_same(u, v)
+    }
+
+    pub fn size(&self, u: usize) -> usize {
+        self.uf.size(u)
+    }
+
+    pub fn group_count(&self) -> usize {
+        self.uf.group_count()
+    }
+
+    /// u, v の属するグループを統合する. 既に同じグループだった場合はfalseを返す
+    pub fn unite(&mut self, u: usize, v: usize) -> bool {
+        let root_u = self.uf.root(u);
+        let root_v = self.uf.root(v);
+        if root_u == root_v {
+            return false;
+        }
+        self.uf.unite(root_u, root_v);
+        let (root, child) = if self.uf.root(root_u) == root_u {
+            (root_u, root_v)
+        } else {
+            (root_v, root_u)
+        };
+        let child_data = self.data[child].take().unwrap();
+        (self.merge)(self.data[root].as_mut().unwrap(), child_data);
+        true
+    }
+
+    /// uが属するグループのデータ
+    pub fn data(&self, u: usize) -> &T {
+        self.data[self.uf.root(u)].as_ref().unwrap()
+    }
+}
+
+#[snippet("union-find-with-data")]
+/// 集合をマージテクで統合する. 要素数の少ない方を多い方に移す
+pub fn merge_sets<T>(a: &mut std::collections::BTreeSet<T>, mut b: std::collections::BTreeSet<T>)
+where
+    T: Ord,
+{
+    if a.len() < b.len() {
+        std::mem::swap(a, &mut b);
     }
+    a.append(&mut b);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::Rng;
 
     #[test]
     fn test_union_find() {
         let mut uf = UnionFind::new(3);
-        assert_eq!(uf.is_same(1, 2), false);
-        assert_eq!(uf.is_same(2, 3), false);
-        assert_eq!(uf.is_same(3, 1), false);
+        assert!(!uf.is_same(1, 2));
+        assert!(!uf.is_same(2, 3));
+        assert!(!uf.is_same(3, 1));
 
         uf.unite(1, 2);
-        assert_eq!(uf.is_same(1, 2), true);
-        assert_eq!(uf.is_same(2, 3), false);
-        assert_eq!(uf.is_same(3, 1), false);
+        assert!(uf.is_same(1, 2));
+        assert!(!uf.is_same(2, 3));
+        assert!(!uf.is_same(3, 1));
 
         uf.unite(2, 3);
-        assert_eq!(uf.is_same(1, 2), true);
-        assert_eq!(uf.is_same(2, 3), true);
-        assert_eq!(uf.is_same(3, 1), true);
+        assert!(uf.is_same(1, 2));
+        assert!(uf.is_same(2, 3));
+        assert!(uf.is_same(3, 1));
+    }
+
+    #[test]
+    fn test_union_find_groups() {
+        let mut uf = UnionFind::new(6);
+        assert_eq!(uf.group_count(), 6);
+        uf.unite(1, 4);
+        uf.unite(6, 4);
+        uf.unite(2, 5);
+        uf.unite(5, 2);
+        assert_eq!(uf.group_count(), 3);
+        assert_eq!(uf.size(6), 3);
+        assert_eq!(uf.size(3), 1);
+        assert_eq!(uf.root(1), uf.root(6));
+        assert_eq!(uf.groups(), vec![vec![1, 4, 6], vec![2, 5], vec![3]]);
+
+        // 空
+        let uf = UnionFind::new(0);
+        assert_eq!(uf.group_count(), 0);
+        assert!(uf.groups().is_empty());
+    }
+
+    #[test]
+    fn test_union_find_path_compression() {
+        // 同じ大きさのグループ同士を根で統合して深さlog nの木を作る
+        // [i, i + 2s)のグループの根はi + 2s - 1になる
+        let n = 1 << 10;
+        let mut uf = UnionFind::new(n);
+        let mut s = 1;
+        while s < n {
+            for i in (1..=n).step_by(2 * s) {
+                uf.unite(i + s - 1, i + 2 * s - 1);
+            }
+            s *= 2;
+        }
+        let depth = |uf: &UnionFind, u: usize| {
+            let mut d = 0;
+            let mut v = u;
+            while uf.parents[v].get() != 0 {
+                v = uf.parents[v].get();
+                d += 1;
+            }
+            d
+        };
+        let deepest = (1..=n).max_by_key(|&u| depth(&uf, u)).unwrap();
+        assert_eq!(depth(&uf, deepest), 10);
+
+        // 根を求めると経路上の頂点は根の直下に付け替えられる
+        let path: Vec<usize> = std::iter::successors(Some(deepest), |&v| {
+            Some(uf.parents[v].get()).filter(|&p| p != 0)
+        })
+        .collect();
+        let r = uf.root(deepest);
+        assert_eq!(r, *path.last().unwrap());
+        for &v in &path[..path.len() - 1] {
+            assert_eq!(uf.parents[v].get(), r);
+        }
+        for u in 1..=n {
+            uf.root(u);
+        }
+        assert!((1..=n).all(|u| depth(&uf, u) <= 1));
+    }
+
+    #[test]
+    fn test_weighted_union_find() {
+        let mut uf: WeightedUnionFind = WeightedUnionFind::new(5);
+        assert_eq!(uf.diff(1, 2), None);
+        assert!(uf.unite(1, 2, 3));
+        assert!(uf.unite(3, 2, -1));
+        assert!(uf.unite(4, 5, 10));
+        assert_eq!(uf.diff(1, 3), Some(4));
+        assert_eq!(uf.diff(3, 1), Some(-4));
+        assert_eq!(uf.diff(2, 2), Some(0));
+        assert!(!uf.is_same(1, 4));
+
+        // 矛盾する制約
+        assert!(!uf.unite(1, 3, 5));
+        assert!(uf.unite(1, 3, 4));
+
+        assert!(uf.unite(3, 5, 1));
+        assert_eq!(uf.diff(1, 4), Some(4 + 1 - 10));
+        assert_eq!(uf.diff(5, 2), Some(-1 - 1));
+        assert!(!uf.unite(4, 2, 0));
+
+        // 実数
+        let mut uf: WeightedUnionFind<f64> = WeightedUnionFind::new(3);
+        assert!(uf.unite(1, 2, 0.5));
+        assert!(uf.unite(2, 3, 0.25));
+        assert_eq!(uf.diff(1, 3), Some(0.75));
+    }
+
+    #[test]
+    fn test_weighted_union_find_path_compression() {
+        // 同じ大きさのグループ同士を根で統合して深さlog nの木を作る
+        let n = 1 << 8;
+        let x = |u: usize| (u * u) as i64;
+        let mut uf: WeightedUnionFind = WeightedUnionFind::new(n);
+        let mut s = 1;
+        while s < n {
+            for i in (1..=n).step_by(2 * s) {
+                let (u, v) = (i + s - 1, i + 2 * s - 1);
+                assert!(uf.unite(u, v, x(v) - x(u)));
+            }
+            s *= 2;
+        }
+        let depth = |uf: &WeightedUnionFind, u: usize| {
+            let mut d = 0;
+            let mut v = u;
+            while uf.parents[v].get() != 0 {
+                v = uf.parents[v].get();
+                d += 1;
+            }
+            d
+        };
+        assert_eq!((1..=n).map(|u| depth(&uf, u)).max(), Some(8));
+
+        // 経路圧縮の後も差が正しく、全ての頂点が根の直下にあること
+        for u in 1..=n {
+            assert_eq!(uf.diff(1, u), Some(x(u) - x(1)));
+        }
+        assert!((1..=n).all(|u| depth(&uf, u) <= 1));
+        for u in 1..=n {
+            assert_eq!(uf.diff(u, n), Some(x(n) - x(u)));
+        }
+    }
+
+    #[test]
+    fn test_rollback_union_find() {
+        let mut uf = RollbackUnionFind::new(5);
+        assert!(uf.unite(1, 2));
+        uf.snapshot();
+        assert!(uf.unite(3, 4));
+        assert!(uf.unite(2, 3));
+        assert!(!uf.unite(1, 4));
+        assert_eq!(uf.size(4), 4);
+        assert_eq!(uf.group_count(), 2);
+        uf.snapshot();
+        assert!(uf.unite(5, 1));
+        assert_eq!(uf.group_count(), 1);
+        uf.rollback();
+        assert!(!uf.is_same(5, 1));
+        assert!(uf.is_same(1, 4));
+        uf.undo();
+        assert!(uf.is_same(1, 4));
+        uf.undo();
+        assert!(!uf.is_same(1, 4));
+        assert!(uf.is_same(3, 4));
+        uf.rollback();
+        assert!(!uf.is_same(3, 4));
+        assert!(uf.is_same(1, 2));
+        assert_eq!(uf.size(1), 2);
+        assert_eq!(uf.group_count(), 4);
+    }
+
+    #[test]
+    fn test_offline_dynamic_connectivity() {
+        // 全探索と一致すること
+        let n = 8;
+        let mut rng = Rng::default();
+        let mut dc = OfflineDynamicConnectivity::new(n);
+        let mut edges: Vec<(usize, usize)> = vec![];
+        let mut expected = vec![];
+        for _ in 0..500 {
+            let u = rng.next_usize(n) + 1;
+            let v = rng.next_usize(n) + 1;
+            match rng.next_below(3) {
+                0 => {
+                    dc.link(u, v);
+                    edges.push((u, v));
+                }
+                1 if !edges.is_empty() => {
+                    let (a, b) = edges.swap_remove(rng.next_usize(edges.len()));
+                    dc.cut(b, a);
+                }
+                _ => {
+                    dc.query(u, v);
+                    let mut uf = UnionFind::new(n);
+                    for &(a, b) in edges.iter() {
+                        uf.unite(a, b);
+                    }
+                    expected.push(uf.is_same(u, v));
+                }
+            }
+        }
+        assert_eq!(dc.solve(), expected);
+
+        // クエリなし
+        let mut dc = OfflineDynamicConnectivity::new(2);
+        dc.link(1, 2);
+        assert!(dc.solve().is_empty());
+    }
+
+    #[test]
+    fn test_partially_persistent_union_find() {
+        let mut uf = PartiallyPersistentUnionFind::new(4);
+        assert!(uf.unite(1, 2));
+        assert!(uf.unite(3, 4));
+        assert!(!uf.unite(2, 1));
+        assert!(uf.unite(2, 4));
+        assert_eq!(uf.now(), 4);
+        assert!(!uf.is_same_at(1, 4, 3));
+        assert!(uf.is_same_at(1, 4, 4));
+        assert!(uf.is_same_at(3, 4, 2));
+        assert_eq!(uf.first_connected_time(1, 2), Some(1));
+        assert_eq!(uf.first_connected_time(1, 3), Some(4));
+        assert_eq!(uf.first_connected_time(3, 3), Some(0));
+        assert_eq!(uf.size_at(1, 0), 1);
+        assert_eq!(uf.size_at(1, 3), 2);
+        assert_eq!(uf.size_at(3, 4), 4);
+
+        // 全探索と一致すること
+        let n = 10;
+        let mut rng = Rng::default();
+        let mut uf = PartiallyPersistentUnionFind::new(n);
+        let mut states = vec![UnionFind::new(n)];
+        let mut edges = vec![];
+        for _ in 0..30 {
+            let (u, v) = (rng.next_usize(n) + 1, rng.next_usize(n) + 1);
+            uf.unite(u, v);
+            edges.push((u, v));
+            let mut cur = UnionFind::new(n);
+            for &(a, b) in edges.iter() {
+                cur.unite(a, b);
+            }
+            states.push(cur);
+        }
+        for (t, state) in states.iter().enumerate() {
+            for u in 1..=n {
+                assert_eq!(uf.size_at(u, t), state.size(u));
+                for v in 1..=n {
+                    assert_eq!(uf.is_same_at(u, v, t), state.is_same(u, v));
+                }
+            }
+        }
+        for u in 1..=n {
+            for v in 1..=n {
+                let expected = (0..states.len()).find(|&t| states[t].is_same(u, v));
+                assert_eq!(uf.first_connected_time(u, v), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_union_find_with_data() {
+        use crate::cumulative_sum::{Additive, Min};
+
+        // 重みの和
+        let mut uf = UnionFindWithData::with_monoid::<Additive<i64>>(vec![3, 1, 4, 1, 5]);
+        assert!(uf.unite(1, 2));
+        assert!(uf.unite(4, 5));
+        assert!(!uf.unite(2, 1));
+        assert_eq!(*uf.data(2), 4);
+        assert_eq!(*uf.data(3), 4);
+        assert_eq!(*uf.data(5), 6);
+        assert!(uf.unite(5, 1));
+        assert_eq!(*uf.data(4), 10);
+        assert_eq!(uf.size(1), 4);
+        assert_eq!(uf.group_count(), 2);
+
+        // 最小の頂点番号
+        let mut uf = UnionFindWithData::with_monoid::<Min<usize>>((1..=4).collect());
+        uf.unite(3, 4);
+        uf.unite(4, 2);
+        assert_eq!(*uf.data(4), 2);
+        assert_eq!(*uf.data(1), 1);
+
+        // 色の集合
+        let colors = [1, 2, 1, 3, 2, 2];
+        let sets = colors
+            .iter()
+            .map(|&c| std::collections::BTreeSet::from([c]))
+            .collect();
+        let mut uf = UnionFindWithData::new(sets, merge_sets);
+        uf.unite(1, 3);
+        uf.unite(2, 5);
+        uf.unite(5, 6);
+        assert_eq!(uf.data(3).len(), 1);
+        assert_eq!(uf.data(6).len(), 1);
+        uf.unite(6, 4);
+        uf.unite(1, 4);
+        assert_eq!(
+            uf.data(1).iter().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
     }
 }
@@ -0,0 +1,356 @@
+use cargo_snippet::snippet;
+
+#[snippet("bit-vector")]
+/// 完備辞書. 0/1の列に対してrankをO(1)で求める
+pub struct BitVector {
+    bits: Vec<u64>,
+    // ranks[i] = bits[0..i]に含まれる1の数
+    ranks: Vec<usize>,
+}
+
+#[snippet("bit-vector")]
+impl BitVector {
+    pub fn new(v: &[bool]) -> Self {
+        let mut bits = vec![0u64; v.len() / 64 + 1];
+        for (i, &b) in v.iter().enumerate() {
+            if b {
+                bits[i / 64] |= 1 << (i % 64);
+            }
+        }
+        let mut ranks = vec![0; bits.len() + 1];
+        for i in 0..bits.len() {
+            ranks[i + 1] = ranks[i] + bits[i].count_ones() as usize;
+        }
+        Self { bits, ranks }
+    }
+
+    /// i番目の値
+    pub fn get(&self, i: usize) -> bool {
+        self.bits[i / 64] >> (i % 64) & 1 == 1
+    }
+
+    /// [0, i)に含まれる1の数
+    pub fn rank1(&self, i: usize) -> usize {
+        let mask = (1u64 << (i % 64)) - 1;
+        self.ranks[i / 64] + (self.bits[i / 64] & mask).count_ones() as usize
+    }
+
+    /// [0, i)に含まれる0の数
+    pub fn rank0(&self, i: usize) -> usize {
+        i - self.rank1(i)
+    }
+}
+
+#[snippet("wavelet-matrix")]
+#[snippet(include = "bit-vector")]
+/// ウェーブレット行列. 非負整数列に対する区間のk番目の値や頻度の取得をO(log V)で行う. インデックスは0-origin
+pub struct WaveletMatrix {
+    n: usize,
+    // ビット数
+    log: usize,
+    // bvs[d]: 上からd番目のビットの列
+    bvs: Vec<BitVector>,
+    // zeros[d]: 上からd番目のビットが0である要素の数
+    zeros: Vec<usize>,
+    // sums[d]: d段目で並び替えた後の列の累積和. with_sumsで構築した場合のみ持つ
+    sums: Option<Vec<Vec<u64>>>,
+}
+
+#[snippet("wavelet-matrix")]
+impl WaveletMatrix {
+    pub fn new(v: &[u64]) -> Self {
+        Self::build(v, false)
+    }
+
+    /// sum_smallest/sum_largestのための累積和も構築する. 値の総和がu64に収まる必要がある
+    pub fn with_sums(v: &[u64]) -> Self {
+        Self::build(v, true)
+    }
+
+    fn build(v: &[u64], with_sums: bool) -> Self {
+        let n = v.len();
+        let max = v.iter().copied().max().unwrap_or(0);
+        let log = (u64::BITS - max.leading_zeros()).max(1) as usize;
+        let mut cur = v.to_vec();
+        let mut bvs = Vec::with_capacity(log);
+        let mut zeros = Vec::with_capacity(log);
+        let mut sums = Vec::with_capacity(log);
+        for d in 0..log {
+            let b = log - 1 - d;
+            let bits: Vec<bool> = cur.iter().map(|&x| x >> b & 1 == 1).collect();
+            bvs.push(BitVector::new(&bits));
+            // ビットが0の要素を前に、1の要素を後ろに安定に並べる
+            let (mut next, ones): (Vec<u64>, Vec<u64>) =
+                cur.iter().partition(|&&x| x >> b & 1 == 0);
+            zeros.push(next.len());
+            next.extend(ones);
+            if with_sums {
+                let mut sum = vec![0; n + 1];
+                for i in 0..n {
+                    sum[i + 1] = sum[i] + next[i];
+                }
+                sums.push(sum);
+            }
+            cur = next;
+        }
+        Self {
+            n,
+            log,
+            bvs,
+            zeros,
+            sums: if with_sums { Some(sums) } else { None },
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// i番目の値
+    pub fn access(&self, i: usize) -> u64 {
+        assert!(i < self.n);
+        let mut i = i;
+        let mut res = 0;
+        for d in 0..self.log {
+            let bv = &self.bvs[d];
+            if bv.get(i) {
+                res |= 1 << (self.log - 1 - d);
+                i = self.zeros[d] + bv.rank1(i);
+            } else {
+                i = bv.rank0(i);
+            }
+        }
+        res
+    }
+
+    /// [0, r)に含まれるxの数
+    pub fn rank(&self, x: u64, r: usize) -> usize {
+        assert!(r <= self.n);
+        if x.checked_shr(self.log as u32).unwrap_or(0) != 0 {
+            return 0;
+        }
+        let (mut l, mut r) = (0, r);
+        for d in 0..self.log {
+            let bv = &self.bvs[d];
+            if x >> (self.log - 1 - d) & 1 == 1 {
+                l = self.zeros[d] + bv.rank1(l);
+                r = self.zeros[d] + bv.rank1(r);
+            } else {
+                l = bv.rank0(l);
+                r = bv.rank0(r);
+            }
+        }
+        r - l
+    }
+
+    /// [l, r)でk番目（0-origin）に小さい値
+    pub fn kth_smallest(&self, l: usize, r: usize, k: usize) -> u64 {
+        assert!(l <= r && r <= self.n);
+        assert!(k < r - l);
+        let (mut l, mut r, mut k) = (l, r, k);
+        let mut res = 0;
+        for d in 0..self.log {
+            let bv = &self.bvs[d];
+            let (l0, r0) = (bv.rank0(l), bv.rank0(r));
+            if k < r0 - l0 {
+                l = l0;
+                r = r0;
+            } else {
+                k -= r0 - l0;
+                res |= 1 << (self.log - 1 - d);
+                l = self.zeros[d] + (l - l0);
+                r = self.zeros[d] + (r - r0);
+            }
+        }
+        res
+    }
+
+    /// [l, r)で小さい方からk個の値の総和. with_sumsで構築している必要がある
+    pub fn sum_smallest(&self, l: usize, r: usize, k: usize) -> u64 {
+        assert!(l <= r && r <= self.n);
+        assert!(k <= r - l);
+        let sums = self
+            .sums
+            .as_ref()
+            .expect("sums are not built; use WaveletMatrix::with_sums");
+        let (mut l, mut r, mut k) = (l, r, k);
+        let mut res = 0;
+        let mut val = 0;
+        for (d, sum) in sums.iter().enumerate() {
+            let bv = &self.bvs[d];
+            let (l0, r0) = (bv.rank0(l), bv.rank0(r));
+            if k <= r0 - l0 {
+                l = l0;
+                r = r0;
+            } else {
+                // ビットが0の要素は全て含まれる
+                res += sum[r0] - sum[l0];
+                k -= r0 - l0;
+                val |= 1 << (self.log - 1 - d);
+                l = self.zeros[d] + (l - l0);
+                r = self.zeros[d] + (r - r0);
+            }
+        }
+        res + val * k as u64
+    }
+
+    /// [l, r)で大きい方からk個の値の総和. with_sumsで構築している必要がある
+    pub fn sum_largest(&self, l: usize, r: usize, k: usize) -> u64 {
+        assert!(l <= r && r <= self.n);
+        assert!(k <= r - l);
+        self.sum_smallest(l, r, r - l) - self.sum_smallest(l, r, r - l - k)
+    }
+
+    /// [l, r)に含まれるx未満の値の数
+    pub fn count_less(&self, l: usize, r: usize, x: u64) -> usize {
+        assert!(l <= r && r <= self.n);
+        if x.checked_shr(self.log as u32).unwrap_or(0) != 0 {
+            return r - l;
+        }
+        let (mut l, mut r) = (l, r);
+        let mut res = 0;
+        for d in 0..self.log {
+            let bv = &self.bvs[d];
+            let (l0, r0) = (bv.rank0(l), bv.rank0(r));
+            if x >> (self.log - 1 - d) & 1 == 1 {
+                res += r0 - l0;
+                l = self.zeros[d] + (l - l0);
+                r = self.zeros[d] + (r - r0);
+            } else {
+                l = l0;
+                r = r0;
+            }
+        }
+        res
+    }
+
+    /// [l, r)に含まれる[lo, hi)の値の数
+    pub fn range_freq(&self, l: usize, r: usize, lo: u64, hi: u64) -> usize {
+        if lo >= hi {
+            return 0;
+        }
+        self.count_less(l, r, hi) - self.count_less(l, r, lo)
+    }
+
+    /// [l, r)に含まれるupper未満の最大の値
+    pub fn prev_value(&self, l: usize, r: usize, upper: u64) -> Option<u64> {
+        let cnt = self.count_less(l, r, upper);
+        if cnt == 0 {
+            None
+        } else {
+            Some(self.kth_smallest(l, r, cnt - 1))
+        }
+    }
+
+    /// [l, r)に含まれるlower以上の最小の値
+    pub fn next_value(&self, l: usize, r: usize, lower: u64) -> Option<u64> {
+        let cnt = self.count_less(l, r, lower);
+        if cnt == r - l {
+            None
+        } else {
+            Some(self.kth_smallest(l, r, cnt))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bit_vector() {
+        let v: Vec<bool> = (0..200).map(|i| i % 3 == 0).collect();
+        let bv = BitVector::new(&v);
+        for i in 0..=200 {
+            let expected = (0..i).filter(|&j| j % 3 == 0).count();
+            assert_eq!(bv.rank1(i), expected);
+            assert_eq!(bv.rank0(i), i - expected);
+        }
+        assert!(bv.get(66));
+        assert!(!bv.get(67));
+    }
+
+    #[test]
+    fn test_wavelet_matrix() {
+        let v: Vec<u64> = vec![5, 4, 5, 5, 2, 1, 5, 6, 1, 3, 5, 0];
+        let wm = WaveletMatrix::with_sums(&v);
+        for (i, &x) in v.iter().enumerate() {
+            assert_eq!(wm.access(i), x);
+        }
+        assert_eq!(wm.rank(5, 12), 5);
+        assert_eq!(wm.rank(5, 3), 2);
+        assert_eq!(wm.rank(7, 12), 0);
+        assert_eq!(wm.rank(100, 12), 0);
+        // 全探索と一致すること
+        for l in 0..=v.len() {
+            for r in l..=v.len() {
+                let mut sorted = v[l..r].to_vec();
+                sorted.sort();
+                for (k, &x) in sorted.iter().enumerate() {
+                    assert_eq!(wm.kth_smallest(l, r, k), x);
+                }
+                for k in 0..=(r - l) {
+                    assert_eq!(wm.sum_smallest(l, r, k), sorted[..k].iter().sum::<u64>());
+                    assert_eq!(
+                        wm.sum_largest(l, r, k),
+                        sorted[(r - l - k)..].iter().sum::<u64>()
+                    );
+                }
+                for lo in 0..8 {
+                    for hi in lo..8 {
+                        let expected = sorted.iter().filter(|&&x| lo <= x && x < hi).count();
+                        assert_eq!(wm.range_freq(l, r, lo, hi), expected);
+                    }
+                    let prev = sorted.iter().copied().filter(|&x| x < lo).max();
+                    assert_eq!(wm.prev_value(l, r, lo), prev);
+                    let next = sorted.iter().copied().filter(|&x| x >= lo).min();
+                    assert_eq!(wm.next_value(l, r, lo), next);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_wavelet_matrix_large_value() {
+        let v: Vec<u64> = vec![1_000_000_000_000, 0, 999_999_999_999, u64::MAX >> 1];
+        let wm = WaveletMatrix::new(&v);
+        assert_eq!(wm.kth_smallest(0, 4, 2), 1_000_000_000_000);
+        assert_eq!(wm.range_freq(0, 4, 1, 1_000_000_000_000), 1);
+        assert_eq!(wm.count_less(0, 4, u64::MAX), 4);
+        assert_eq!(wm.next_value(0, 3, 1), Some(999_999_999_999));
+
+        // 全て0
+        let wm = WaveletMatrix::new(&[0, 0, 0]);
+        assert_eq!(wm.kth_smallest(0, 3, 1), 0);
+        assert_eq!(wm.rank(0, 3), 3);
+
+        // 空
+        let wm = WaveletMatrix::new(&[]);
+        assert_eq!(wm.prev_value(0, 0, 10), None);
+    }
+
+    #[test]
+    fn test_wavelet_matrix_sum_overflow() {
+        // 総和がu64に収まらなくても、累積和を持たなければ構築できること
+        let v = vec![1_000_000_000_000_000_000; 20];
+        let wm = WaveletMatrix::new(&v);
+        assert_eq!(wm.kth_smallest(0, 20, 10), 1_000_000_000_000_000_000);
+        assert_eq!(wm.rank(1_000_000_000_000_000_000, 20), 20);
+
+        // 範囲の総和がu64に収まる限りは累積和も使えること
+        let v = vec![1_000_000_000_000_000_000; 18];
+        let wm = WaveletMatrix::with_sums(&v);
+        assert_eq!(wm.sum_largest(0, 18, 18), 18_000_000_000_000_000_000);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_wavelet_matrix_sum_without_sums() {
+        let wm = WaveletMatrix::new(&[3, 1, 2]);
+        wm.sum_smallest(0, 3, 2);
+    }
+}